@@ -0,0 +1,165 @@
+//! Instructions per second through the core, with nothing else in the way.
+//!
+//! `decode` measures turning opcodes into `Instruction`s, `execute` runs
+//! already decoded instructions, `fetch` compares decoding every
+//! instruction as it runs with looking it up by address as the decode cache
+//! does, and `frames` runs whole frames through `cycle`. Each runs the instructions one ROM actually runs, so the mix is
+//! realistic. See the README for how to compare against earlier numbers.
+
+use chip_oxide::{ChipConfig, ChipOxide, Instruction};
+use common::NullIO;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+// Frames run per iteration.
+const FRAMES: u64 = 60;
+
+// Instructions traced from each ROM for `decode` and `execute`.
+const TRACE: usize = 60 * 1000;
+
+const ROMS: [(&str, &[u8]); 3] = [
+    ("pong", include_bytes!("../roms/PONG")),
+    ("test_opcode", include_bytes!("../roms/test_opcode.ch8")),
+    ("bc_test", include_bytes!("../roms/bc_test.ch8")),
+];
+
+// Run every instruction of every frame, even in busy-waits.
+fn config() -> ChipConfig {
+    ChipConfig {
+        instructions_per_second: 60 * 1000,
+        skip_idle: false,
+        ..ChipConfig::default(false)
+    }
+}
+
+// The address and opcode of the first `TRACE` instructions a ROM runs. One
+// instruction a frame, so delay timer waits do not fill the trace.
+fn trace(rom: &[u8]) -> Vec<(u16, u16)> {
+    let config = ChipConfig {
+        instructions_per_second: 60,
+        ..config()
+    };
+    let mut io = NullIO;
+    let mut chip8 = ChipOxide::new(rom, &mut io, &config).unwrap();
+    (0..TRACE)
+        .map(|_| {
+            let address = chip8.counter();
+            let bytes = &chip8.memory()[address..address + 2];
+            let opcode = (bytes[0] as u16) << 8 | bytes[1] as u16;
+            chip8.cycle().unwrap();
+            (address as u16, opcode)
+        })
+        .collect()
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(TRACE as u64));
+    for (name, rom) in ROMS {
+        let opcodes: Vec<u16> = trace(rom).into_iter().map(|(_, opcode)| opcode).collect();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for &opcode in &opcodes {
+                    black_box(Instruction::try_from(black_box(opcode)).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn execute(c: &mut Criterion) {
+    let config = config();
+    let mut group = c.benchmark_group("execute");
+    group.throughput(Throughput::Elements(TRACE as u64));
+    for (name, rom) in ROMS {
+        let insts: Vec<(u16, Instruction)> = trace(rom)
+            .into_iter()
+            .map(|(address, opcode)| (address, Instruction::try_from(opcode).unwrap()))
+            .collect();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut io = NullIO;
+                let mut chip8 = ChipOxide::new(rom, &mut io, &config).unwrap();
+                for &(address, inst) in &insts {
+                    chip8.execute_at(address, black_box(inst)).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn fetch(c: &mut Criterion) {
+    let config = config();
+    let mut group = c.benchmark_group("fetch");
+    group.throughput(Throughput::Elements(TRACE as u64));
+    for (name, rom) in ROMS {
+        let trace = trace(rom);
+        group.bench_function(format!("{}_uncached", name), |b| {
+            b.iter(|| {
+                let mut io = NullIO;
+                let mut chip8 = ChipOxide::new(rom, &mut io, &config).unwrap();
+                for &(address, opcode) in &trace {
+                    let inst = Instruction::try_from(black_box(opcode)).unwrap();
+                    chip8.execute_at(address, inst).unwrap();
+                }
+            })
+        });
+        // One entry per instruction address, like the decode cache.
+        let mut cache = vec![None; 0x1000 / 2];
+        for &(address, opcode) in &trace {
+            cache[address as usize / 2] = Instruction::try_from(opcode).ok();
+        }
+        group.bench_function(format!("{}_cached", name), |b| {
+            b.iter(|| {
+                let mut io = NullIO;
+                let mut chip8 = ChipOxide::new(rom, &mut io, &config).unwrap();
+                for &(address, _) in &trace {
+                    let inst = black_box(&cache)[address as usize / 2].unwrap();
+                    chip8.execute_at(address, inst).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn frames(c: &mut Criterion) {
+    let config = config();
+    // Compiled blocks are built as the program runs, so their cost is
+    // counted too.
+    #[cfg(feature = "jit")]
+    let jit = ChipConfig {
+        engine: chip_oxide::Engine::Jit,
+        ..config
+    };
+    let mut group = c.benchmark_group("frames");
+    group.throughput(Throughput::Elements(FRAMES * 1000));
+    let engines = [
+        ("", &config),
+        #[cfg(feature = "jit")]
+        ("_jit", &jit),
+    ];
+    for (name, rom) in ROMS {
+        // From the start each time, so the whole program is measured rather
+        // than whatever loop it ends up in.
+        for (suffix, config) in engines {
+            group.bench_function(format!("{}{}", name, suffix), |b| {
+                b.iter(|| {
+                    let mut io = NullIO;
+                    let mut chip8 = ChipOxide::new(rom, &mut io, config).unwrap();
+                    for _ in 0..FRAMES {
+                        chip8.cycle().unwrap();
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode, execute, fetch, frames);
+criterion_main!(benches);
@@ -0,0 +1,196 @@
+//! Generate the ROM database, src/database/roms.rs:
+//! `database [programs.json] [hints.json]`.
+//!
+//! The input is a list of programs in the format of the community CHIP-8
+//! database, https://github.com/chip-8/chip-8-database. By default this reads
+//! data/roms.json, which only describes the ROMs in roms/ and is not a copy
+//! of that database. Pass the database's own programs.json to bundle every
+//! ROM it knows. hints.json adds what that format has no field for, by
+//! SHA-1.
+
+use chip_oxide::{Platform, Quirks};
+use serde_json::{Map, Value};
+use std::{
+    env::args,
+    fs::{read_to_string, write},
+    io::Error,
+    process::exit,
+};
+
+const PROGRAMS: &str = "data/roms.json";
+const HINTS: &str = "data/hints.json";
+const OUTPUT: &str = "src/database/roms.rs";
+
+/// The Rust source of the database, from the contents of programs.json and
+/// hints.json.
+pub fn generate(programs: &str, hints: &str) -> Result<String, Error> {
+    let programs: Vec<Value> = serde_json::from_str(programs)?;
+    let hints: Map<String, Value> = serde_json::from_str(hints)?;
+    let mut entries = vec![];
+    for program in &programs {
+        let title = program["title"].as_str().unwrap_or("Untitled");
+        let authors: Vec<&str> = program["authors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let Some(roms) = program["roms"].as_object() else {
+            continue;
+        };
+        for (sha1, rom) in roms {
+            // The first platform the ROM runs on that the emulator has.
+            let Some((id, platform)) = rom["platforms"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .find_map(|id| Some((id, platform(id)?)))
+            else {
+                continue;
+            };
+            let overrides = &rom["quirkyPlatforms"][id];
+            let quirks = match overrides.as_object() {
+                Some(overrides) => {
+                    format!("Some(crate::{:?})", with_overrides(platform, overrides))
+                }
+                None => "None".into(),
+            };
+            let keys: Vec<(&str, u64)> = rom["keys"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, key)| Some((name.as_str(), key.as_u64()?)))
+                .filter(|&(_, key)| key < 16)
+                .collect();
+            let bindings: Vec<(&str, u64)> = keys
+                .iter()
+                .filter_map(|&(name, key)| Some((host_key(name)?, key)))
+                .collect();
+            let colors = match rom["colors"]["pixels"].as_array().map(Vec::as_slice) {
+                Some([background, foreground, ..]) => {
+                    match (color(background), color(foreground)) {
+                        (Some(background), Some(foreground)) => {
+                            format!("Some([0x{:06X}, 0x{:06X}])", background, foreground)
+                        }
+                        _ => "None".into(),
+                    }
+                }
+                _ => "None".into(),
+            };
+            let hint = hints.get(sha1).map(|hints| hints["blend"].clone());
+            let hint = hint.unwrap_or_default();
+            let blend = match (hint["or"].as_u64(), hint["phosphor"].as_u64()) {
+                (Some(n), _) => format!("Blend::Or({})", n),
+                (_, Some(persistence)) => {
+                    format!(
+                        "Blend::Phosphor {{ persistence: {} }}",
+                        persistence.min(255)
+                    )
+                }
+                _ => "Blend::None".into(),
+            };
+            let author = match authors.is_empty() {
+                true => "None".into(),
+                false => format!("Some({:?})", authors.join(", ")),
+            };
+            entries.push((
+                sha1.to_lowercase(),
+                format!(
+                    "    RomInfo {{\n        sha1: {:?},\n        title: {:?},\n        \
+                     author: {},\n        platform: Platform::{:?},\n        quirks: {},\n        \
+                     cycles_per_frame: {:?},\n        keys: &{:?},\n        \
+                     bindings: &{:?},\n        colors: {},\n        blend: {},\n    }},\n",
+                    sha1.to_lowercase(),
+                    title,
+                    author,
+                    platform,
+                    quirks,
+                    rom["tickrate"].as_u64(),
+                    keys,
+                    bindings,
+                    colors,
+                    blend
+                ),
+            ));
+        }
+    }
+    // Sorted, so lookups can search.
+    entries.sort();
+    entries.dedup_by(|a, b| a.0 == b.0);
+    let mut out = String::from(
+        "// Generated by `cargo run --example database`. Do not edit; regenerate it\n\
+         // instead.\n\n\
+         use super::RomInfo;\n\
+         use crate::{Blend, Platform};\n\n\
+         // Sorted by SHA-1.\n\
+         pub(super) static ROMS: &[RomInfo] = &[\n",
+    );
+    for (_, entry) in entries {
+        out += &entry;
+    }
+    out += "];\n";
+    Ok(out)
+}
+
+// The emulator's platform for one of the database's.
+fn platform(id: &str) -> Option<Platform> {
+    Some(match id {
+        "originalChip8" | "hybridVIP" | "chip8x" => Platform::Chip8,
+        "modernChip8" | "chip48" | "superchip1" | "superchip" => Platform::SChip,
+        "xochip" => Platform::XoChip,
+        _ => return None,
+    })
+}
+
+// A platform's quirks with a ROM's exceptions applied. The
+// database's `vblank` and `memoryIncrementByX` have no switch here.
+fn with_overrides(platform: Platform, overrides: &Map<String, Value>) -> Quirks {
+    let mut quirks = Quirks::for_platform(platform);
+    for (name, value) in overrides {
+        let Some(on) = value.as_bool() else {
+            continue;
+        };
+        match name.as_str() {
+            "shift" => quirks.shift_vy = !on,
+            "jump" => quirks.jump_v0 = !on,
+            "memoryLeaveIUnchanged" => quirks.memory_increment = !on,
+            "logic" => quirks.vf_reset = on,
+            "wrap" => quirks.wrap = on,
+            _ => {}
+        }
+    }
+    quirks
+}
+
+// The host key to bind a game's key to by default: the arrows for moving,
+// space for the main action.
+fn host_key(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "up" => "up",
+        "down" => "down",
+        "left" => "left",
+        "right" => "right",
+        "a" => "space",
+        _ => return None,
+    })
+}
+
+// `#RRGGBB` as a number.
+fn color(value: &Value) -> Option<u32> {
+    u32::from_str_radix(value.as_str()?.strip_prefix('#')?, 16).ok()
+}
+
+fn run() -> Result<(), Error> {
+    let programs = args().nth(1).unwrap_or_else(|| PROGRAMS.into());
+    let hints = args().nth(2).unwrap_or_else(|| HINTS.into());
+    let source = generate(&read_to_string(programs)?, &read_to_string(hints)?)?;
+    write(OUTPUT, source)
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
@@ -0,0 +1,31 @@
+//! Recompile a ROM to a Rust module: `recompile <rom> [output.rs]`. The
+//! module is written to stdout when no output path is given.
+
+use chip_oxide::recompile;
+use std::{
+    env::args,
+    fs::{read, write},
+    io::Error,
+    process::exit,
+};
+
+fn run() -> Result<(), Error> {
+    let Some(path) = args().nth(1) else {
+        return Err(Error::other("Usage: recompile <rom> [output.rs]"));
+    };
+    let source = recompile(&read(path)?);
+    match args().nth(2) {
+        Some(output) => write(output, source),
+        None => {
+            print!("{}", source);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
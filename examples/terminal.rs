@@ -1,20 +1,31 @@
-use chip_oxide::{ChipConfig, ChipIO, ChipOxide, SCREEN_HEIGHT, SCREEN_WIDTH};
+use chip_oxide::{
+    Blend, ChipConfig, ChipIO, ChipOxide, Command, Error, FrameBlender, Framebuffer, Keymap,
+    Keypad, Layout, Rect, RomInfo, SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+#[cfg(feature = "capture")]
+use chip_oxide::{Recorder, Renderer};
 
 const HEIGHT: u16 = SCREEN_HEIGHT as u16;
 const WIDTH: u16 = SCREEN_WIDTH as u16;
 
 use std::{
-    fs::read as fread,
-    io::{stdout, Error, ErrorKind, Write},
+    collections::VecDeque,
+    env::args,
+    fs::{read as fread, File},
+    io::{stdout, Write},
     ops::Drop,
+    process::exit,
     time::Duration,
 };
 
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{poll, read, Event, KeyCode, KeyEventKind},
+    event::{
+        poll, read, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute, queue,
-    style::Print,
+    style::{Color, Colors, Print, ResetColor, SetColors},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, SetTitle},
 };
 
@@ -22,32 +33,62 @@ fn chip_oxide_cli<W>(console: &mut W) -> Result<(), Error>
 where
     W: Write,
 {
-    let program = fread("/home/abhi/Dev/Rust/chip_oxide/roms/PONG").unwrap();
-    let mut io = TerminalIO::new(console)?;
-    let mut config = ChipConfig::default(false);
-    ChipOxide::start(&program[..], &mut io, &mut config)?;
+    let path = args().nth(1).unwrap_or_else(|| "roms/PONG".into());
+    let program = fread(path)?;
+    // A layout name or a keymap file, QWERTY by default.
+    let keymap = match args().nth(2) {
+        Some(arg) => match arg.parse::<Layout>() {
+            Ok(layout) => Keymap::preset(layout),
+            Err(_) => Keymap::load(arg)?,
+        },
+        None => Keymap::preset(Layout::Qwerty),
+    };
+    let config = ChipConfig::for_rom(&program);
+    let keymap = match config.rom {
+        Some(info) => keymap.with_overrides(info),
+        None => keymap,
+    };
+    let mut io = TerminalIO::new(console, keymap, config.rom)?;
+    ChipOxide::start(&program[..], &mut io, &config)?;
     Ok(())
 }
 
 struct TerminalIO<W: Write> {
     write: W,
-    prev: [[bool; SCREEN_HEIGHT]; SCREEN_WIDTH],
+    keymap: Keymap,
+    colors: Colors,
     x: u16,
     y: u16,
+    screen: Framebuffer,
+    blender: FrameBlender,
+    #[cfg(feature = "capture")]
+    recorder: Option<Recorder>,
+    captures: usize,
+    // Whether the terminal reports key releases.
+    releases: bool,
+    speed: u32,
+    keys: VecDeque<(usize, bool)>,
+    commands: VecDeque<Command>,
 }
 
 impl<W> TerminalIO<W>
 where
     W: Write,
 {
-    fn new(mut write: W) -> Result<Self, Error> {
+    fn new(mut write: W, keymap: Keymap, rom: Option<&RomInfo>) -> Result<Self, Error> {
         terminal::enable_raw_mode()?;
         let (x, y) = terminal::size()?;
         if x < (WIDTH + 5) {
             terminal::disable_raw_mode()?;
-            return Err(Error::new(ErrorKind::Other, "Small Terminal"));
+            return Err(Error::other("Small Terminal"));
         };
-        queue!(write, SetTitle("Chip Oxide"), EnterAlternateScreen, Hide,)?;
+        queue!(
+            write,
+            SetTitle("Chip Oxide"),
+            EnterAlternateScreen,
+            Hide,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
+        )?;
         let x = (x / 2) - (WIDTH / 2) - 2;
         let y = (y / 2) - (HEIGHT / 2) - 2;
         queue!(
@@ -73,13 +114,125 @@ where
         }
         write.flush()?;
         let (x, y) = (x + 2, y + 2);
+        // The game's own colours, or the terminal's.
+        let colors = match rom.and_then(|info| info.colors) {
+            Some([background, foreground]) => Colors::new(rgb(foreground), rgb(background)),
+            None => Colors {
+                foreground: None,
+                background: None,
+            },
+        };
         Ok(Self {
             write,
+            keymap,
+            colors,
             x,
             y,
-            prev: [[false; SCREEN_HEIGHT]; SCREEN_WIDTH],
+            screen: Framebuffer::default(),
+            blender: FrameBlender::new(rom.map_or(Blend::None, |info| info.blend)),
+            #[cfg(feature = "capture")]
+            recorder: None,
+            captures: 0,
+            releases: false,
+            speed: 100,
+            keys: VecDeque::new(),
+            commands: VecDeque::new(),
         })
     }
+
+    // Read every pending terminal event, queueing key changes and commands.
+    fn pump(&mut self) -> Result<(), Error> {
+        while poll(Duration::ZERO)? {
+            let Event::Key(event) = read()? else {
+                continue;
+            };
+            match event.kind {
+                KeyEventKind::Release => self.releases = true,
+                KeyEventKind::Press => {
+                    if let Some(command) = self.command(event.code) {
+                        self.commands.push_back(command);
+                        continue;
+                    }
+                }
+                KeyEventKind::Repeat => {}
+            }
+            let key = match event.code {
+                KeyCode::F(12) if event.kind == KeyEventKind::Press => {
+                    self.screenshot()?;
+                    continue;
+                }
+                KeyCode::F(11) if event.kind == KeyEventKind::Press => {
+                    self.toggle_recording()?;
+                    continue;
+                }
+                code => match host_key(code).and_then(|host| self.keymap.get(&host)) {
+                    Some(key) => key as usize,
+                    None => continue,
+                },
+            };
+            self.keys
+                .push_back((key, event.kind != KeyEventKind::Release));
+            // Without release events a key could never come up again, so
+            // treat every press as a tap.
+            if !self.releases {
+                self.keys.push_back((key, false));
+            }
+        }
+        Ok(())
+    }
+
+    // The emulator command bound to a key, if any.
+    fn command(&mut self, code: KeyCode) -> Option<Command> {
+        self.speed = match code {
+            KeyCode::Esc => return Some(Command::Exit),
+            KeyCode::F(1) => return Some(Command::TogglePause),
+            KeyCode::F(2) => return Some(Command::Step),
+            KeyCode::F(3) => return Some(Command::Reset),
+            KeyCode::PageUp => (self.speed * 2).min(800),
+            KeyCode::PageDown => (self.speed / 2).max(25),
+            KeyCode::Home => 100,
+            _ => return None,
+        };
+        Some(Command::Speed(self.speed))
+    }
+
+    // Save the screen as a PNG, or a PBM without the capture feature.
+    fn screenshot(&mut self) -> Result<(), Error> {
+        self.captures += 1;
+        #[cfg(feature = "capture")]
+        {
+            let image = Renderer::new(8).render(&self.screen);
+            let file = File::create(format!("chip_oxide_{}.png", self.captures))?;
+            Ok(chip_oxide::write_png(&image, file)?)
+        }
+        #[cfg(not(feature = "capture"))]
+        {
+            let file = File::create(format!("chip_oxide_{}.pbm", self.captures))?;
+            Ok(chip_oxide::write_pbm(&self.screen, file)?)
+        }
+    }
+
+    // Start recording, or stop and save the recording as a GIF.
+    #[cfg(feature = "capture")]
+    fn toggle_recording(&mut self) -> Result<(), Error> {
+        let Some(recorder) = self.recorder.take() else {
+            self.recorder = Some(Recorder::new(Renderer::new(4)));
+            return Ok(());
+        };
+        self.captures += 1;
+        let file = File::create(format!("chip_oxide_{}.gif", self.captures))?;
+        Ok(recorder.write_gif(file)?)
+    }
+
+    // A recording could not be saved without the capture feature, so only
+    // say so.
+    #[cfg(not(feature = "capture"))]
+    fn toggle_recording(&mut self) -> Result<(), Error> {
+        Ok(execute!(
+            self.write,
+            SetTitle("Chip Oxide: recording needs the capture feature")
+        )?)
+    }
 }
 
 impl<W> Drop for TerminalIO<W>
@@ -87,7 +240,14 @@ where
     W: Write,
 {
     fn drop(&mut self) {
-        execute!(self.write, Show, LeaveAlternateScreen).unwrap();
+        execute!(
+            self.write,
+            PopKeyboardEnhancementFlags,
+            ResetColor,
+            Show,
+            LeaveAlternateScreen
+        )
+        .unwrap();
         terminal::disable_raw_mode().unwrap();
     }
 }
@@ -96,26 +256,32 @@ impl<W> ChipIO for TerminalIO<W>
 where
     W: Write,
 {
-    fn update_screen(
-        &mut self,
-        screen: &[[bool; SCREEN_HEIGHT]; SCREEN_WIDTH],
-    ) -> Result<(), Error> {
-        let mut x = 0;
-        for row in screen {
-            let mut y = 0;
-            for pixel in row {
-                queue!(self.write, MoveTo(self.x + x, self.y + y))?;
-                if *pixel {
-                    queue!(self.write, Print("█"))?;
-                } else if self.prev[x as usize][y as usize] {
-                    queue!(self.write, Print(" "))?;
+    fn present_frame(&mut self, screen: &Framebuffer, damage: &[Rect]) -> Result<(), Error> {
+        queue!(self.write, SetColors(self.colors))?;
+        self.blender.push(screen);
+        // Blending changes pixels the program did not touch.
+        let whole = [Rect::new(0, 0, screen.width(), screen.height())];
+        let damage = match self.blender.blend() {
+            Blend::None => damage,
+            _ => &whole,
+        };
+        for rect in damage {
+            for y in rect.y..rect.y + rect.height {
+                queue!(
+                    self.write,
+                    MoveTo(self.x + rect.x as u16, self.y + y as u16)
+                )?;
+                for x in rect.x..rect.x + rect.width {
+                    queue!(self.write, Print(shade(self.blender.intensity(x, y))))?;
                 }
-                y += 1;
             }
-            x += 1
         }
         self.write.flush().unwrap();
-        self.prev = screen.to_owned();
+        self.screen.clone_from(screen);
+        #[cfg(feature = "capture")]
+        if let Some(recorder) = &mut self.recorder {
+            recorder.push(screen);
+        }
         Ok(())
     }
     fn start_beep(&mut self) -> Result<(), Error> {
@@ -124,46 +290,57 @@ where
     fn end_beep(&mut self) -> Result<(), Error> {
         Ok(())
     }
-    fn get_key(&mut self) -> Result<Option<(usize, bool)>, Error> {
-        if poll(Duration::from_micros(1))? {
-            if let Event::Key(event) = read()? {
-                if event.code == KeyCode::Esc {
-                    panic!("You Quit!")
-                } else if let KeyCode::Char(c) = event.code {
-                    return Ok(Some((
-                        match c {
-                            '1' => 1,
-                            '2' => 2,
-                            '3' => 3,
-                            '4' => 0xC,
-                            'q' => 4,
-                            'w' => 5,
-                            'e' => 6,
-                            'r' => 0xD,
-                            'a' => 7,
-                            's' => 8,
-                            'd' => 9,
-                            'f' => 0xE,
-                            'z' => 0xA,
-                            'x' => 0,
-                            'c' => 0xB,
-                            'v' => 0xF,
-                            _ => return Ok(None),
-                        },
-                        match event.kind {
-                            KeyEventKind::Press => true,
-                            KeyEventKind::Repeat => true,
-                            KeyEventKind::Release => false,
-                        },
-                    )));
-                }
-            }
+    fn update_keypad(&mut self, keypad: &mut Keypad) -> Result<(), Error> {
+        self.pump()?;
+        for (key, down) in self.keys.drain(..) {
+            keypad.set(key, down)?;
         }
-        Ok(None)
+        Ok(())
+    }
+    fn next_command(&mut self) -> Result<Option<Command>, Error> {
+        self.pump()?;
+        Ok(self.commands.pop_front())
+    }
+}
+
+// The keymap name of a terminal key.
+fn host_key(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(' ') => "space",
+        KeyCode::Char(c) => return Some(c.to_lowercase().collect()),
+        KeyCode::Up => "up",
+        KeyCode::Down => "down",
+        KeyCode::Left => "left",
+        KeyCode::Right => "right",
+        KeyCode::Enter => "enter",
+        KeyCode::Tab => "tab",
+        KeyCode::Backspace => "backspace",
+        _ => return None,
+    };
+    Some(name.into())
+}
+
+// A block as bright as a blended pixel.
+fn shade(intensity: u8) -> &'static str {
+    match intensity {
+        0 => " ",
+        1..=0x55 => "░",
+        0x56..=0xAA => "▒",
+        0xAB..=0xFE => "▓",
+        0xFF => "█",
     }
 }
 
+// A terminal colour from `0xRRGGBB`.
+fn rgb(color: u32) -> Color {
+    let [_, r, g, b] = color.to_be_bytes();
+    Color::Rgb { r, g, b }
+}
+
 fn main() {
     let mut console = stdout();
-    chip_oxide_cli(&mut console).unwrap();
+    if let Err(error) = chip_oxide_cli(&mut console) {
+        eprintln!("{}", error);
+        exit(1);
+    }
 }
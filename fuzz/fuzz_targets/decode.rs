@@ -0,0 +1,11 @@
+#![no_main]
+
+use chip_oxide::Instruction;
+use libfuzzer_sys::fuzz_target;
+
+// Every 16 bit word either decodes or is rejected, never panics.
+fuzz_target!(|data: &[u8]| {
+    for word in data.chunks_exact(2) {
+        let _ = Instruction::try_from(u16::from_be_bytes([word[0], word[1]]));
+    }
+});
@@ -0,0 +1,36 @@
+#![no_main]
+
+use chip_oxide::{ChipConfig, ChipOxide, KeyEvent, MockIO};
+use libfuzzer_sys::fuzz_target;
+
+// Frames to run each input for, at the default 8 opcodes per frame.
+const FRAMES: usize = 120;
+
+// Input layout: a flags byte, a key event count, two bytes per key event
+// (cycle, then key with the press flag in the top bit), then the ROM.
+fuzz_target!(|data: &[u8]| {
+    let [flags, count, rest @ ..] = data else {
+        return;
+    };
+    let split = (*count as usize * 2).min(rest.len());
+    let (keys, program) = rest.split_at(split);
+
+    let config = ChipConfig::default(flags & 1 == 1);
+    let mut io = MockIO::new();
+    for (i, event) in keys.chunks_exact(2).enumerate() {
+        io.script(KeyEvent {
+            cycle: (i * 64) as u64 + event[0] as u64,
+            key: (event[1] & 0x7F) as usize,
+            pressed: event[1] & 0x80 != 0,
+        });
+    }
+
+    let Ok(mut chip8) = ChipOxide::new(program, &mut io, &config) else {
+        return;
+    };
+    for _ in 0..FRAMES {
+        if chip8.cycle().is_err() {
+            break;
+        }
+    }
+});
@@ -0,0 +1,595 @@
+use crate::{
+    Instruction, Platform, Quirks, COUNTER_START, INSTRUCTION_SIZE, MEM_SIZE, SCREEN_HEIGHT,
+    SCREEN_WIDTH, VF,
+};
+use alloc::{vec, vec::Vec};
+
+// How far ahead of an instruction to look for code that depends on it.
+const LOOKAHEAD: usize = 16;
+
+/// One of the `Quirks` switches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quirk {
+    ShiftVy,
+    JumpV0,
+    MemoryIncrement,
+    VfReset,
+    Wrap,
+}
+
+/// How sure the analyser is about a suggestion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    High,
+}
+
+/// A quirk-sensitive pattern found in reachable code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// 8XY6 or 8XYE with X and Y different.
+    ShiftXY,
+    /// BXNN with X other than 0.
+    OffsetJump,
+    /// FX55 or FX65, then code that uses I before setting it again.
+    IndexAfterMemory,
+    /// 8XY1, 8XY2 or 8XY3, then code that reads VF before setting it again.
+    FlagAfterLogic,
+    /// A sprite drawn at a constant position that crosses the screen edge.
+    EdgeSprite,
+    /// An opcode only SUPER-CHIP understands.
+    SuperChip,
+    /// An opcode only XO-CHIP understands.
+    XoChip,
+}
+
+impl Quirk {
+    // The switch in `quirks` this stands for.
+    fn switch(self, quirks: &mut Quirks) -> &mut bool {
+        match self {
+            Quirk::ShiftVy => &mut quirks.shift_vy,
+            Quirk::JumpV0 => &mut quirks.jump_v0,
+            Quirk::MemoryIncrement => &mut quirks.memory_increment,
+            Quirk::VfReset => &mut quirks.vf_reset,
+            Quirk::Wrap => &mut quirks.wrap,
+        }
+    }
+}
+
+impl Pattern {
+    /// The quirk switch that changes what this pattern does.
+    pub fn quirk(&self) -> Option<Quirk> {
+        match self {
+            Pattern::ShiftXY => Some(Quirk::ShiftVy),
+            Pattern::OffsetJump => Some(Quirk::JumpV0),
+            Pattern::IndexAfterMemory => Some(Quirk::MemoryIncrement),
+            Pattern::FlagAfterLogic => Some(Quirk::VfReset),
+            Pattern::EdgeSprite => Some(Quirk::Wrap),
+            Pattern::SuperChip | Pattern::XoChip => None,
+        }
+    }
+}
+
+/// A pattern and the address of the instruction it starts at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub address: u16,
+    pub pattern: Pattern,
+}
+
+/// A quirk profile guessed from the code, with how sure each switch is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub platform: Platform,
+    pub quirks: Quirks,
+    /// Confidence for every switch the program is sensitive to.
+    pub confidence: Vec<(Quirk, Confidence)>,
+}
+
+/// Static analysis of the code reachable from the start of a ROM.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    memory: [u8; MEM_SIZE],
+    reachable: Vec<u16>,
+    findings: Vec<Finding>,
+}
+
+impl Analysis {
+    /// Trace every path from the entry point and look for quirk-sensitive code.
+    pub fn of(rom: &[u8]) -> Self {
+        let mut memory = [0; MEM_SIZE];
+        let len = rom.len().min(MEM_SIZE - COUNTER_START);
+        memory[COUNTER_START..COUNTER_START + len].copy_from_slice(&rom[..len]);
+        let mut analysis = Self {
+            memory,
+            reachable: vec![],
+            findings: vec![],
+        };
+        analysis.trace();
+        for address in analysis.reachable.clone() {
+            analysis.inspect(address);
+        }
+        analysis.findings.sort_by_key(|f| f.address);
+        analysis
+    }
+
+    /// Addresses of every reachable instruction, in order.
+    pub fn reachable(&self) -> &[u16] {
+        &self.reachable
+    }
+
+    /// Every quirk-sensitive pattern, in address order.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// The config switches that change how this program behaves.
+    pub fn sensitive_to(&self) -> Vec<Quirk> {
+        let mut quirks: Vec<Quirk> = self
+            .findings
+            .iter()
+            .filter_map(|f| f.pattern.quirk())
+            .collect();
+        quirks.sort();
+        quirks.dedup();
+        quirks
+    }
+
+    /// The platform the program seems to be written for.
+    pub fn platform(&self) -> Platform {
+        let has = |pattern| self.findings.iter().any(|f| f.pattern == pattern);
+        if has(Pattern::XoChip) {
+            Platform::XoChip
+        } else if has(Pattern::SuperChip) {
+            Platform::SChip
+        } else {
+            Platform::Chip8
+        }
+    }
+
+    /// Guess a quirk profile. Each switch the program depends on is set
+    /// from the code that depends on it, where that code shows which way it
+    /// was written, and from the platform otherwise. A switch is rated high
+    /// when all of its evidence agrees, or when there is none but extension
+    /// opcodes pin the platform down; plain CHIP-8 code could have been
+    /// written against any interpreter.
+    pub fn suggest(&self) -> Suggestion {
+        let platform = self.platform();
+        let mut quirks = Quirks::for_platform(platform);
+        let mut confidence = vec![];
+        for quirk in self.sensitive_to() {
+            let votes: Vec<bool> = self
+                .findings
+                .iter()
+                .filter(|f| f.pattern.quirk() == Some(quirk))
+                .filter_map(|f| self.evidence(f))
+                .collect();
+            let on = votes.iter().filter(|&&on| on).count();
+            let off = votes.len() - on;
+            let switch = quirk.switch(&mut quirks);
+            let sure = match (on, off) {
+                (0, 0) => platform != Platform::Chip8,
+                (_, 0) | (0, _) => {
+                    *switch = on > 0;
+                    true
+                }
+                // Mixed evidence: go with the majority, or the platform
+                // on a tie.
+                _ => {
+                    if on != off {
+                        *switch = on > off;
+                    }
+                    false
+                }
+            };
+            let sure = if sure {
+                Confidence::High
+            } else {
+                Confidence::Low
+            };
+            confidence.push((quirk, sure));
+        }
+        Suggestion {
+            platform,
+            quirks,
+            confidence,
+        }
+    }
+
+    // Which way a finding says its switch should be set, if it shows.
+    fn evidence(&self, finding: &Finding) -> Option<bool> {
+        let inst = Instruction::try_from(self.opcode(finding.address as usize)?).ok()?;
+        match (finding.pattern, inst) {
+            // Setting up VY for a shift means it is the source; setting up
+            // VX means it is shifted in place.
+            (Pattern::ShiftXY, Instruction::ShiftRight(x, y) | Instruction::ShiftLeft(x, y)) => {
+                self.last_written(finding.address, [x, y]).map(|r| r == y)
+            }
+            // Likewise BXNN adds whichever of V0 and VX was set up for it.
+            (Pattern::OffsetJump, Instruction::OffsetJump(x, _)) => {
+                self.last_written(finding.address, [0, x]).map(|r| r == 0)
+            }
+            // Saving or loading again carries on from where the last one
+            // stopped only if I moved; drawing or storing digits at I means
+            // it was expected to stay put.
+            (Pattern::IndexAfterMemory, _) => {
+                let mut rest = self.following(finding.address);
+                match rest.find(|inst| uses_index(inst) || sets_index(inst))? {
+                    Instruction::Save(_) | Instruction::Load(_) => Some(true),
+                    Instruction::Draw(..) | Instruction::AsDecimal(_) => Some(false),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Which of two registers the straight-line code before an instruction
+    // wrote last, if it wrote exactly one of them.
+    fn last_written(&self, address: u16, registers: [u8; 2]) -> Option<u8> {
+        let mut address = address as usize;
+        for _ in 0..LOOKAHEAD {
+            address = address.checked_sub(INSTRUCTION_SIZE)?;
+            self.reachable.binary_search(&(address as u16)).ok()?;
+            let inst = Instruction::try_from(self.opcode(address)?).ok()?;
+            if branches(&inst) {
+                return None;
+            }
+            match registers.map(|r| writes(&inst, r)) {
+                [true, false] => return Some(registers[0]),
+                [false, true] => return Some(registers[1]),
+                [true, true] => return None,
+                [false, false] => {}
+            }
+        }
+        None
+    }
+
+    fn opcode(&self, address: usize) -> Option<u16> {
+        Some(u16::from_be_bytes([
+            *self.memory.get(address)?,
+            *self.memory.get(address + 1)?,
+        ]))
+    }
+
+    // Walk every path from the entry point, recording reachable addresses
+    // and the extension opcodes met on the way.
+    fn trace(&mut self) {
+        let mut seen = vec![false; MEM_SIZE];
+        let mut pending = vec![COUNTER_START];
+        while let Some(address) = pending.pop() {
+            if address >= MEM_SIZE || seen[address] {
+                continue;
+            }
+            let Some(opcode) = self.opcode(address) else {
+                continue;
+            };
+            seen[address] = true;
+            let next = address + INSTRUCTION_SIZE;
+            let inst = match Instruction::try_from(opcode) {
+                Ok(inst) => inst,
+                Err(_) => {
+                    if let Some((pattern, size)) = extension(opcode) {
+                        self.findings.push(Finding {
+                            address: address as u16,
+                            pattern,
+                        });
+                        self.reachable.push(address as u16);
+                        pending.push(address + size);
+                    }
+                    continue;
+                }
+            };
+            self.reachable.push(address as u16);
+            if let Instruction::Exit
+            | Instruction::LowRes
+            | Instruction::HighRes
+            | Instruction::Draw(_, _, 0) = inst
+            {
+                self.findings.push(Finding {
+                    address: address as u16,
+                    pattern: Pattern::SuperChip,
+                });
+            }
+            match inst {
+                Instruction::Jump(nnn) => pending.push(nnn as usize),
+                Instruction::SubRoutine(nnn) => pending.extend([nnn as usize, next]),
+                Instruction::Return | Instruction::OffsetJump(..) | Instruction::Exit => {}
+                Instruction::SkipED(..)
+                | Instruction::SkipNED(..)
+                | Instruction::SkipER(..)
+                | Instruction::SkipNER(..)
+                | Instruction::KeyPressed(_)
+                | Instruction::KeyReleased(_) => pending.extend([next, next + INSTRUCTION_SIZE]),
+                _ => pending.push(next),
+            }
+        }
+        self.reachable.sort();
+    }
+
+    // The straight-line code after an instruction, up to and including the
+    // first branch.
+    fn following(&self, address: u16) -> impl Iterator<Item = Instruction> + '_ {
+        let mut address = address as usize;
+        let mut done = false;
+        core::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            address += INSTRUCTION_SIZE;
+            let inst = Instruction::try_from(self.opcode(address)?).ok()?;
+            done = branches(&inst);
+            Some(inst)
+        })
+        .take(LOOKAHEAD)
+    }
+
+    fn inspect(&mut self, address: u16) {
+        let Some(Ok(inst)) = self.opcode(address as usize).map(Instruction::try_from) else {
+            return;
+        };
+        let pattern = match inst {
+            Instruction::ShiftRight(x, y) | Instruction::ShiftLeft(x, y) if x != y => {
+                Some(Pattern::ShiftXY)
+            }
+            Instruction::OffsetJump(x, _) if x != 0 => Some(Pattern::OffsetJump),
+            Instruction::Save(_) | Instruction::Load(_) => {
+                let mut rest = self.following(address);
+                rest.find(|inst| uses_index(inst) || sets_index(inst))
+                    .filter(uses_index)
+                    .map(|_| Pattern::IndexAfterMemory)
+            }
+            Instruction::BinaryOR(x, _)
+            | Instruction::BinaryAND(x, _)
+            | Instruction::LogicalXOR(x, _) => {
+                let mut rest = self.following(address);
+                let read_later = rest
+                    .find(|inst| reads(inst, VF as u8) || writes(inst, VF as u8))
+                    .is_some_and(|inst| reads(&inst, VF as u8));
+                (x as usize == VF || read_later).then_some(Pattern::FlagAfterLogic)
+            }
+            Instruction::SetRegisterD(r, value) => self
+                .following(address)
+                .take_while(|inst| !writes(inst, r))
+                .find_map(|inst| match inst {
+                    Instruction::Draw(x, y, n) => {
+                        let past_x = x == r && value as usize + 8 > SCREEN_WIDTH;
+                        let past_y = y == r && value as usize + n as usize > SCREEN_HEIGHT;
+                        (past_x || past_y).then_some(Pattern::EdgeSprite)
+                    }
+                    _ => None,
+                }),
+            _ => None,
+        };
+        if let Some(pattern) = pattern {
+            self.findings.push(Finding { address, pattern });
+        }
+    }
+}
+
+// The pattern and size of an extension opcode that does not decode. The
+// ones that do are found by `trace`.
+fn extension(opcode: u16) -> Option<(Pattern, usize)> {
+    let x = (opcode >> 8) & 0xF;
+    match (opcode >> 12, x, opcode & 0xFF) {
+        (0x0, 0, 0xFB | 0xFC) => Some((Pattern::SuperChip, 2)),
+        (0x0, 0, nn) if nn >> 4 == 0xC => Some((Pattern::SuperChip, 2)),
+        (0xF, _, 0x30 | 0x75 | 0x85) => Some((Pattern::SuperChip, 2)),
+        (0x0, 0, nn) if nn >> 4 == 0xD => Some((Pattern::XoChip, 2)),
+        (0x5, _, nn) if nn & 0xF == 2 || nn & 0xF == 3 => Some((Pattern::XoChip, 2)),
+        (0xF, 0, 0x00) => Some((Pattern::XoChip, 4)),
+        (0xF, _, 0x01 | 0x02 | 0x3A) => Some((Pattern::XoChip, 2)),
+        _ => None,
+    }
+}
+
+// Whether control can leave the straight line at this instruction.
+fn branches(inst: &Instruction) -> bool {
+    matches!(
+        inst,
+        Instruction::Return
+            | Instruction::Exit
+            | Instruction::Jump(_)
+            | Instruction::SubRoutine(_)
+            | Instruction::OffsetJump(..)
+            | Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..)
+            | Instruction::KeyPressed(_)
+            | Instruction::KeyReleased(_)
+    )
+}
+
+fn uses_index(inst: &Instruction) -> bool {
+    matches!(
+        inst,
+        Instruction::Draw(..)
+            | Instruction::AddIndex(_)
+            | Instruction::AsDecimal(_)
+            | Instruction::Save(_)
+            | Instruction::Load(_)
+    )
+}
+
+fn sets_index(inst: &Instruction) -> bool {
+    matches!(inst, Instruction::SetIndex(_) | Instruction::GetFont(_))
+}
+
+fn reads(inst: &Instruction, r: u8) -> bool {
+    match *inst {
+        Instruction::SkipED(x, _)
+        | Instruction::SkipNED(x, _)
+        | Instruction::AddRegisterD(x, _)
+        | Instruction::OffsetJump(x, _)
+        | Instruction::KeyPressed(x)
+        | Instruction::KeyReleased(x)
+        | Instruction::SetDelay(x)
+        | Instruction::SetSound(x)
+        | Instruction::AddIndex(x)
+        | Instruction::GetFont(x)
+        | Instruction::AsDecimal(x) => x == r,
+        Instruction::SetRegisterR(_, y) => y == r,
+        Instruction::SkipER(x, y)
+        | Instruction::SkipNER(x, y)
+        | Instruction::BinaryOR(x, y)
+        | Instruction::BinaryAND(x, y)
+        | Instruction::LogicalXOR(x, y)
+        | Instruction::AddRegisterR(x, y)
+        | Instruction::SubtractXY(x, y)
+        | Instruction::ShiftRight(x, y)
+        | Instruction::SubtractYX(x, y)
+        | Instruction::ShiftLeft(x, y)
+        | Instruction::Draw(x, y, _) => x == r || y == r,
+        Instruction::Save(x) => r <= x,
+        _ => false,
+    }
+}
+
+fn writes(inst: &Instruction, r: u8) -> bool {
+    match *inst {
+        Instruction::SetRegisterD(x, _)
+        | Instruction::AddRegisterD(x, _)
+        | Instruction::SetRegisterR(x, _)
+        | Instruction::BinaryOR(x, _)
+        | Instruction::BinaryAND(x, _)
+        | Instruction::LogicalXOR(x, _)
+        | Instruction::Random(x, _)
+        | Instruction::GetDelay(x)
+        | Instruction::KeyWait(x) => x == r,
+        Instruction::AddRegisterR(x, _)
+        | Instruction::SubtractXY(x, _)
+        | Instruction::ShiftRight(x, _)
+        | Instruction::SubtractYX(x, _)
+        | Instruction::ShiftLeft(x, _) => x == r || r as usize == VF,
+        Instruction::Draw(..) => r as usize == VF,
+        Instruction::Load(x) => r <= x,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(rom: &[u8]) -> Vec<Pattern> {
+        Analysis::of(rom)
+            .findings()
+            .iter()
+            .map(|f| f.pattern)
+            .collect()
+    }
+
+    #[test]
+    fn finds_quirk_sensitive_code() {
+        // 8016, then loop forever.
+        assert_eq!(patterns(&[0x80, 0x16, 0x12, 0x02]), [Pattern::ShiftXY]);
+        // F165, then draw from I without setting it.
+        assert_eq!(
+            patterns(&[0xF1, 0x65, 0xD0, 0x15, 0x12, 0x04]),
+            [Pattern::IndexAfterMemory]
+        );
+        // 8121, then skip on VF.
+        assert_eq!(
+            patterns(&[0x81, 0x21, 0x3F, 0x00, 0x12, 0x04, 0x12, 0x06]),
+            [Pattern::FlagAfterLogic]
+        );
+        // V0 = 60, draw at x = 60.
+        assert_eq!(
+            patterns(&[0x60, 0x3C, 0xD0, 0x15, 0x12, 0x04]),
+            [Pattern::EdgeSprite]
+        );
+    }
+
+    #[test]
+    fn ignores_harmless_code() {
+        // 8006, and F165 followed by a fresh ANNN.
+        let rom = [0x80, 0x06, 0xF1, 0x65, 0xA2, 0x00, 0xD0, 0x15, 0x12, 0x08];
+        let analysis = Analysis::of(&rom);
+        assert!(analysis.findings().is_empty());
+        assert_eq!(analysis.reachable(), [0x200, 0x202, 0x204, 0x206, 0x208]);
+    }
+
+    #[test]
+    fn extension_opcodes_pin_the_platform() {
+        // Switch to hires, BXNN, then loop.
+        let analysis = Analysis::of(&[0x00, 0xFF, 0xB1, 0x00]);
+        assert_eq!(analysis.platform(), Platform::SChip);
+        let suggestion = analysis.suggest();
+        assert_eq!(suggestion.quirks, Quirks::SCHIP);
+        assert_eq!(suggestion.confidence, [(Quirk::JumpV0, Confidence::High)]);
+    }
+
+    #[test]
+    fn decoded_extension_opcodes_are_found() {
+        // DXY0, 00FE, 00FF and 00FD, each followed by a loop.
+        for rom in [
+            [0xD0, 0x10, 0x12, 0x02],
+            [0x00, 0xFE, 0x12, 0x02],
+            [0x00, 0xFF, 0x12, 0x02],
+            [0x00, 0xFD, 0x12, 0x02],
+        ] {
+            let analysis = Analysis::of(&rom);
+            assert_eq!(patterns(&rom), [Pattern::SuperChip], "{:02X?}", rom);
+            assert_eq!(analysis.platform(), Platform::SChip);
+        }
+        // 00FD ends the program, so the loop is never reached.
+        assert_eq!(Analysis::of(&[0x00, 0xFD, 0x12, 0x02]).reachable(), [0x200]);
+    }
+
+    fn suggest(rom: &[u8]) -> Suggestion {
+        Analysis::of(rom).suggest()
+    }
+
+    #[test]
+    fn quirks_follow_the_code() {
+        // V1 = 5, then V0 = V1 >> 1: VY is the source.
+        let shift = suggest(&[0x61, 0x05, 0x80, 0x16, 0x12, 0x04]);
+        assert!(shift.quirks.shift_vy);
+        assert_eq!(shift.confidence, [(Quirk::ShiftVy, Confidence::High)]);
+        // V0 = 5, then shift it in place.
+        let shift = suggest(&[0x60, 0x05, 0x80, 0x16, 0x12, 0x04]);
+        assert!(!shift.quirks.shift_vy);
+        assert_eq!(shift.confidence, [(Quirk::ShiftVy, Confidence::High)]);
+
+        // V0 = 4, then B300 jumps with it.
+        let jump = suggest(&[0x60, 0x04, 0xB3, 0x00]);
+        assert!(jump.quirks.jump_v0);
+        assert_eq!(jump.confidence, [(Quirk::JumpV0, Confidence::High)]);
+        // V3 = 4, then B300 jumps with it.
+        let jump = suggest(&[0x63, 0x04, 0xB3, 0x00]);
+        assert!(!jump.quirks.jump_v0);
+        assert_eq!(jump.confidence, [(Quirk::JumpV0, Confidence::High)]);
+
+        // Two loads in a row read on from where the first stopped.
+        let memory = suggest(&[0xF1, 0x65, 0xF1, 0x65, 0x12, 0x04]);
+        assert!(memory.quirks.memory_increment);
+        // A draw after a load expects I where it was.
+        let memory = suggest(&[0xF1, 0x65, 0xD0, 0x15, 0x12, 0x04]);
+        assert!(!memory.quirks.memory_increment);
+        assert_eq!(
+            memory.confidence,
+            [(Quirk::MemoryIncrement, Confidence::High)]
+        );
+    }
+
+    #[test]
+    fn each_switch_is_rated_on_its_own() {
+        // V0 = 4, a shift with nothing set up for it, then B300.
+        let suggestion = suggest(&[0x60, 0x04, 0x82, 0x36, 0xB3, 0x00]);
+        assert_eq!(suggestion.quirks, Quirks::COSMAC);
+        assert_eq!(
+            suggestion.confidence,
+            [
+                (Quirk::ShiftVy, Confidence::Low),
+                (Quirk::JumpV0, Confidence::High)
+            ]
+        );
+        // A load followed by another, and one followed by a draw.
+        let rom = [0xF1, 0x65, 0xF1, 0x65, 0xF1, 0x65, 0xD0, 0x15, 0x12, 0x08];
+        assert_eq!(
+            suggest(&rom).confidence,
+            [(Quirk::MemoryIncrement, Confidence::Low)]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::Framebuffer;
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+/// How consecutive frames are combined to hide XOR flicker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blend {
+    /// Show every frame as it is.
+    #[default]
+    None,
+    /// Light a pixel if it was lit in any of the last `n` frames.
+    Or(usize),
+    /// Light pixels fully, then let them fade like a phosphor screen,
+    /// keeping `persistence / 255` of their brightness each frame.
+    Phosphor { persistence: u8 },
+}
+
+/// Per-pixel brightness built up from the frames fed to it.
+#[derive(Debug, Clone)]
+pub struct FrameBlender {
+    blend: Blend,
+    history: VecDeque<Framebuffer>,
+    width: usize,
+    intensity: Vec<u8>,
+}
+
+impl FrameBlender {
+    pub fn new(blend: Blend) -> Self {
+        Self {
+            blend,
+            history: VecDeque::new(),
+            width: 0,
+            intensity: vec![],
+        }
+    }
+
+    pub fn blend(&self) -> Blend {
+        self.blend
+    }
+
+    /// Change the blend mode, forgetting earlier frames.
+    pub fn set_blend(&mut self, blend: Blend) {
+        *self = Self::new(blend);
+    }
+
+    /// Feed the next frame, usually once per `present_frame`.
+    pub fn push(&mut self, screen: &Framebuffer) {
+        let (width, height) = (screen.width(), screen.height());
+        if self.width != width || self.intensity.len() != width * height {
+            self.history.clear();
+            self.width = width;
+            self.intensity = vec![0; width * height];
+        }
+        if let Blend::Or(n) = self.blend {
+            if self.history.len() >= n.max(1) {
+                self.history.pop_front();
+            }
+            self.history.push_back(screen.clone());
+        }
+        for (i, level) in self.intensity.iter_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            *level = match self.blend {
+                _ if screen.get(x, y) => 0xFF,
+                Blend::None => 0,
+                Blend::Or(_) => {
+                    if self.history.iter().any(|frame| frame.get(x, y)) {
+                        0xFF
+                    } else {
+                        0
+                    }
+                }
+                Blend::Phosphor { persistence } => {
+                    (*level as u16 * persistence as u16 / 0xFF) as u8
+                }
+            };
+        }
+    }
+
+    /// Brightness of a pixel from 0 to 255. Out of range pixels are dark.
+    pub fn intensity(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width {
+            return 0;
+        }
+        self.intensity.get(y * self.width + x).copied().unwrap_or(0)
+    }
+
+    /// Every brightness, row-major.
+    pub fn intensities(&self) -> &[u8] {
+        &self.intensity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(lit: &[bool]) -> Vec<Framebuffer> {
+        lit.iter()
+            .map(|&on| {
+                let mut screen = Framebuffer::default();
+                screen.set(0, 0, 0, on);
+                screen
+            })
+            .collect()
+    }
+
+    fn levels(blend: Blend, lit: &[bool]) -> Vec<u8> {
+        let mut blender = FrameBlender::new(blend);
+        frames(lit)
+            .iter()
+            .map(|frame| {
+                blender.push(frame);
+                blender.intensity(0, 0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blends_the_last_frames() {
+        let lit = [true, false, false, true, false];
+        assert_eq!(levels(Blend::None, &lit), [255, 0, 0, 255, 0]);
+        assert_eq!(levels(Blend::Or(2), &lit), [255, 255, 0, 255, 255]);
+    }
+
+    #[test]
+    fn phosphor_fades_out() {
+        let lit = [true, false, false, true];
+        let blend = Blend::Phosphor { persistence: 128 };
+        assert_eq!(levels(blend, &lit), [255, 128, 64, 255]);
+    }
+}
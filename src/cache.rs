@@ -0,0 +1,89 @@
+use crate::{Instruction, INSTRUCTION_SIZE, MEM_SIZE};
+use alloc::boxed::Box;
+use core::ops::Range;
+
+/// Decoded instructions by address, so hot code is only decoded once.
+///
+/// Only even addresses are cached, since almost all code is aligned. Writes
+/// to memory must invalidate the bytes they touch.
+#[derive(Debug, Clone)]
+pub(crate) struct DecodeCache {
+    entries: Box<[Option<Instruction>; MEM_SIZE / INSTRUCTION_SIZE]>,
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        Self {
+            entries: Box::new([None; MEM_SIZE / INSTRUCTION_SIZE]),
+        }
+    }
+}
+
+impl DecodeCache {
+    pub(crate) fn get(&self, address: usize) -> Option<Instruction> {
+        if address % INSTRUCTION_SIZE != 0 {
+            return None;
+        }
+        *self.entries.get(address / INSTRUCTION_SIZE)?
+    }
+
+    pub(crate) fn insert(&mut self, address: usize, inst: Instruction) {
+        if address % INSTRUCTION_SIZE == 0 {
+            if let Some(entry) = self.entries.get_mut(address / INSTRUCTION_SIZE) {
+                *entry = Some(inst);
+            }
+        }
+    }
+
+    /// Forget instructions overlapping the bytes written.
+    pub(crate) fn invalidate(&mut self, written: Range<usize>) {
+        let first = written.start / INSTRUCTION_SIZE;
+        let last = written
+            .end
+            .div_ceil(INSTRUCTION_SIZE)
+            .min(self.entries.len());
+        if first < last {
+            self.entries[first..last].fill(None);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChipConfig, ChipOxide, MockIO};
+
+    #[test]
+    fn invalidates_overlapping_entries() {
+        let mut cache = DecodeCache::default();
+        for address in [0x200, 0x202, 0x204] {
+            cache.insert(address, Instruction::Clear);
+        }
+        cache.insert(0x201, Instruction::Clear);
+        assert_eq!(cache.get(0x201), None);
+        cache.invalidate(0x203..0x204);
+        assert_eq!(cache.get(0x200), Some(Instruction::Clear));
+        assert_eq!(cache.get(0x202), None);
+        assert_eq!(cache.get(0x204), Some(Instruction::Clear));
+    }
+
+    #[test]
+    fn sees_self_modifying_code() {
+        // V1 += 1, then overwrite its low byte with 5 and loop back to it.
+        let program = [0x71, 0x01, 0xA2, 0x01, 0x60, 0x05, 0xF0, 0x55, 0x12, 0x00];
+        let config = ChipConfig {
+            instructions_per_second: 60 * 5,
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.registers()[1], 1);
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.registers()[1], 6);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{Framebuffer, Image, Renderer};
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{Error, Write};
+
+// Frames per second of recordings, the rate `present_frame` is called at.
+#[cfg(feature = "capture")]
+const FPS: u32 = 60;
+
+/// Write the screen as a binary PBM, lit pixels black.
+#[cfg(feature = "std")]
+pub fn write_pbm<W: Write>(screen: &Framebuffer, mut out: W) -> Result<(), Error> {
+    let mut bits = screen.packed(0).to_vec();
+    for plane in 1..screen.planes() {
+        for (bit, other) in bits.iter_mut().zip(screen.packed(plane)) {
+            *bit |= other;
+        }
+    }
+    write!(out, "P4\n{} {}\n", screen.width(), screen.height())?;
+    out.write_all(&bits)
+}
+
+/// Write an image as a PNG.
+#[cfg(feature = "capture")]
+pub fn write_png<W: Write>(image: &Image, out: W) -> Result<(), Error> {
+    let mut encoder = png_encoder(image, out);
+    encoder.set_compression(png::Compression::Best);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.pixels)?;
+    Ok(writer.finish()?)
+}
+
+/// Records presented frames for an animation, merging runs of identical
+/// frames into one longer frame.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    renderer: Renderer,
+    // Each distinct frame and how many 60 Hz frames it was shown for.
+    frames: Vec<(Framebuffer, u32)>,
+}
+
+impl Recorder {
+    pub fn new(renderer: Renderer) -> Self {
+        Self {
+            renderer,
+            frames: vec![],
+        }
+    }
+
+    /// Add the next 60 Hz frame.
+    pub fn push(&mut self, screen: &Framebuffer) {
+        match self.frames.last_mut() {
+            Some((last, count)) if last == screen => *count += 1,
+            _ => self.frames.push((screen.clone(), 1)),
+        }
+    }
+
+    /// Number of distinct frames kept.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Length of the recording in 60 Hz frames.
+    pub fn duration(&self) -> u32 {
+        self.frames.iter().map(|(_, count)| count).sum()
+    }
+
+    /// The distinct frames, rendered, with how long each is shown for.
+    /// Every image is the size of the largest, so a recording can span a
+    /// change of resolution: smaller screens are scaled up to fit.
+    pub fn images(&self) -> impl Iterator<Item = (Image, u32)> + '_ {
+        let (width, height) = self.frames.iter().fold((0, 0), |(w, h), (screen, _)| {
+            let (width, height) = self.renderer.size(screen);
+            (w.max(width), h.max(height))
+        });
+        self.frames.iter().map(move |(screen, count)| {
+            let (w, h) = self.renderer.size(screen);
+            let renderer = Renderer {
+                scale: self.renderer.scale() * (width / w).min(height / h),
+                ..self.renderer
+            };
+            let image = renderer.render(screen);
+            let background = self.renderer.palette.colors[0];
+            (pad(image, width, height, background), *count)
+        })
+    }
+
+    /// Write the recording as an animated PNG, with exact 60 Hz timing.
+    #[cfg(feature = "capture")]
+    pub fn write_apng<W: Write>(&self, out: W) -> Result<(), Error> {
+        let Some((first, _)) = self.images().next() else {
+            return Err(Error::other("Nothing recorded"));
+        };
+        let mut encoder = png_encoder(&first, out);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        let mut writer = encoder.write_header()?;
+        for (image, count) in self.images() {
+            writer.set_frame_delay(count.min(u16::MAX as u32) as u16, FPS as u16)?;
+            writer.write_image_data(&image.pixels)?;
+        }
+        Ok(writer.finish()?)
+    }
+
+    /// Write the recording as a looping GIF. GIF delays are in hundredths
+    /// of a second, so frame times are rounded, carrying the error forward.
+    #[cfg(feature = "capture")]
+    pub fn write_gif<W: Write>(&self, out: W) -> Result<(), Error> {
+        let Some((first, _)) = self.images().next() else {
+            return Err(Error::other("Nothing recorded"));
+        };
+        let (width, height) = (first.width as u16, first.height as u16);
+        let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(Error::other)?;
+        let (mut elapsed, mut shown) = (0, 0);
+        for (image, count) in self.images() {
+            elapsed += count;
+            let end = (elapsed * 100 + FPS / 2) / FPS;
+            let (palette, indices) = indexed(&image)?;
+            let mut frame = gif::Frame::from_palette_pixels(width, height, indices, palette, None);
+            frame.delay = (end - shown).min(u16::MAX as u32) as u16;
+            shown = end;
+            encoder.write_frame(&frame).map_err(Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+// An image grown to `width` by `height`, filled out with the background.
+fn pad(image: Image, width: usize, height: usize, background: u32) -> Image {
+    if (image.width, image.height) == (width, height) {
+        return image;
+    }
+    let rgba = [
+        (background >> 16) as u8,
+        (background >> 8) as u8,
+        background as u8,
+        0xFF,
+    ];
+    let mut pixels = rgba.repeat(width * height);
+    for (y, row) in image.pixels.chunks_exact(image.width * 4).enumerate() {
+        let start = y * width * 4;
+        pixels[start..start + row.len()].copy_from_slice(row);
+    }
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(feature = "capture")]
+fn png_encoder<W: Write>(image: &Image, out: W) -> png::Encoder<'static, W> {
+    let mut encoder = png::Encoder::new(out, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+}
+
+// An RGB palette and an index per pixel, for a GIF frame.
+#[cfg(feature = "capture")]
+fn indexed(image: &Image) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let mut colors: Vec<&[u8]> = vec![];
+    let mut indices = Vec::with_capacity(image.width * image.height);
+    for pixel in image.pixels.chunks_exact(4) {
+        let rgb = &pixel[..3];
+        let index = match colors.iter().position(|&c| c == rgb) {
+            Some(index) => index,
+            None if colors.len() < 256 => {
+                colors.push(rgb);
+                colors.len() - 1
+            }
+            None => return Err(Error::other("Too many colours for a GIF")),
+        };
+        indices.push(index as u8);
+    }
+    Ok((colors.concat(), indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Resolution;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writes_pbm() {
+        let mut screen = Framebuffer::default();
+        screen.set(0, 0, 0, true);
+        screen.set(0, 63, 31, true);
+        let mut out = vec![];
+        write_pbm(&screen, &mut out).unwrap();
+        let header = b"P4\n64 32\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 64 * 32 / 8);
+        assert_eq!(out[header.len()], 0x80);
+        assert_eq!(out.last(), Some(&0x01));
+    }
+
+    #[test]
+    fn merges_repeated_frames() {
+        let mut recorder = Recorder::new(Renderer::default());
+        let mut screen = Framebuffer::default();
+        recorder.push(&screen);
+        recorder.push(&screen);
+        screen.set(0, 1, 1, true);
+        recorder.push(&screen);
+        assert_eq!(recorder.len(), 2);
+        assert_eq!(recorder.duration(), 3);
+        let counts: Vec<u32> = recorder.images().map(|(_, count)| count).collect();
+        assert_eq!(counts, [2, 1]);
+    }
+
+    #[cfg(feature = "capture")]
+    #[test]
+    fn writes_png_apng_and_gif() {
+        let mut recorder = Recorder::new(Renderer::new(2));
+        let mut screen = Framebuffer::default();
+        for i in 0..4 {
+            screen.set(0, i, i, true);
+            recorder.push(&screen);
+        }
+        let (image, _) = recorder.images().next().unwrap();
+        let mut png = vec![];
+        write_png(&image, &mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+        let mut apng = vec![];
+        recorder.write_apng(&mut apng).unwrap();
+        assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+
+        let mut gif = vec![];
+        recorder.write_gif(&mut gif).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+    }
+
+    #[test]
+    fn scales_frames_to_the_largest_resolution() {
+        let mut recorder = Recorder::new(Renderer::new(2));
+        let mut screen = Framebuffer::default();
+        screen.set(0, 1, 1, true);
+        recorder.push(&screen);
+        screen.set_resolution(Resolution::High);
+        recorder.push(&screen);
+        let images: Vec<Image> = recorder.images().map(|(image, _)| image).collect();
+        assert_eq!((images[0].width, images[0].height), (256, 128));
+        assert_eq!((images[1].width, images[1].height), (256, 128));
+        // The low resolution pixel at (1, 1) covers 4x4 image pixels.
+        assert_ne!(images[0].get(4, 4), images[0].get(3, 3));
+        assert_eq!(images[0].get(4, 4), images[0].get(7, 7));
+
+        #[cfg(feature = "capture")]
+        {
+            let mut gif = vec![];
+            recorder.write_gif(&mut gif).unwrap();
+            let mut apng = vec![];
+            recorder.write_apng(&mut apng).unwrap();
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{thread::sleep, time::Instant};
+
+/// A monotonic source of time for the `Scheduler`.
+pub trait Clock {
+    /// Time since some fixed point. Never goes backwards.
+    fn now(&self) -> Duration;
+
+    /// Wait for `duration` to pass.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// The system's monotonic clock.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct RealClock {
+    start: Instant,
+}
+
+#[cfg(feature = "std")]
+impl RealClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for RealClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        sleep(duration)
+    }
+}
+
+/// A clock that only moves when told to, for tests. Sleeping moves it
+/// forward by exactly the time asked for.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ManualClock {
+    now: Duration,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration)
+    }
+}
@@ -1,17 +1,157 @@
+use crate::{Analysis, RomInfo, Timing};
+
+/// The machine a program was written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Chip8,
+    SChip,
+    XoChip,
+}
+
+/// Behaviours that differ between CHIP-8 interpreters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6 and 8XYE shift VY into VX, instead of shifting VX in place.
+    pub shift_vy: bool,
+    /// BNNN jumps to NNN + V0, instead of XNN + VX.
+    pub jump_v0: bool,
+    /// FX55 and FX65 leave I pointing past the last register they touch.
+    pub memory_increment: bool,
+    /// 8XY1, 8XY2 and 8XY3 reset VF to zero.
+    pub vf_reset: bool,
+    /// Sprites crossing the screen edge wrap to the other side, instead of
+    /// being clipped. Start coordinates wrap either way.
+    pub wrap: bool,
+    /// FX0A waits for a key to be pressed and then released, instead of
+    /// finishing as soon as it goes down.
+    pub key_release: bool,
+}
+
+impl Quirks {
+    /// The original COSMAC VIP interpreter.
+    pub const COSMAC: Self = Self {
+        shift_vy: true,
+        jump_v0: true,
+        memory_increment: true,
+        vf_reset: true,
+        wrap: false,
+        key_release: true,
+    };
+
+    /// SUPER-CHIP 1.1 on the HP 48.
+    pub const SCHIP: Self = Self {
+        shift_vy: false,
+        jump_v0: false,
+        memory_increment: false,
+        vf_reset: false,
+        wrap: false,
+        key_release: false,
+    };
+
+    /// XO-CHIP, as implemented by Octo.
+    pub const XO_CHIP: Self = Self {
+        shift_vy: true,
+        jump_v0: true,
+        memory_increment: true,
+        vf_reset: false,
+        wrap: true,
+        key_release: false,
+    };
+
+    /// Quirks of the interpreter for a platform.
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Chip8 => Self::COSMAC,
+            Platform::SChip => Self::SCHIP,
+            Platform::XoChip => Self::XO_CHIP,
+        }
+    }
+}
+
+/// How instructions are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Engine {
+    /// Fetch, decode and execute one instruction at a time.
+    #[default]
+    Interpreter,
+    /// Compile basic blocks that run often to native code with Cranelift,
+    /// and interpret the rest. Needs `Timing::Fixed`, `ChipOxide::new`
+    /// refuses any other timing.
+    #[cfg(feature = "jit")]
+    Jit,
+}
+
 /// Struct for configuring the emulator.
 pub struct ChipConfig {
-    pub opcodes_per_cycle: usize,
+    /// CPU speed.
+    pub instructions_per_second: u32,
+    /// Rate of the timers, and of frames.
     pub timer_hz: u8,
-    pub legacy: bool,
+    /// How long instructions take.
+    pub timing: Timing,
+    /// How instructions are run.
+    pub engine: Engine,
+    /// End a frame early when the program busy-waits for the next frame:
+    /// jumping to itself, waiting for a key with FX0A, or polling a running
+    /// delay timer with `FX07; 3X00; 1NNN`. Saves host CPU without changing
+    /// what the program sees. Turn it off to run every instruction.
+    pub skip_idle: bool,
+    pub quirks: Quirks,
+    /// Present the screen after every sprite draw, not only once per frame.
+    pub present_on_draw: bool,
+    /// Seed for CXNN, so runs can be repeated. With `None` each machine
+    /// gets its own from `std`. Without `std` there is nothing to take one
+    /// from, so `None` always means the same seed and CXNN is completely
+    /// predictable: set one, say from a hardware random number generator,
+    /// for games to play differently each time.
+    pub seed: Option<u64>,
+    /// The database entry for the ROM, from `for_rom`. Frontends take the
+    /// game's keys, colours and blending from it.
+    pub rom: Option<&'static RomInfo>,
 }
 
 impl ChipConfig {
-    /// Default Config
+    /// Default Config, with COSMAC quirks if `legacy` and SUPER-CHIP ones otherwise.
     pub fn default(legacy: bool) -> Self {
         Self {
-            opcodes_per_cycle: 8,
+            instructions_per_second: 480,
             timer_hz: 60,
-            legacy,
+            timing: Timing::Fixed,
+            engine: Engine::Interpreter,
+            skip_idle: true,
+            present_on_draw: false,
+            seed: None,
+            rom: None,
+            quirks: if legacy {
+                Quirks::COSMAC
+            } else {
+                Quirks::SCHIP
+            },
+        }
+    }
+
+    /// Config for a ROM, from the database if it is known and from
+    /// analysing its code otherwise.
+    pub fn for_rom(rom: &[u8]) -> Self {
+        match RomInfo::find(rom) {
+            Some(info) => {
+                let default = Self::default(false);
+                // One frame per timer tick.
+                let per_second = |cycles| cycles as u32 * default.timer_hz as u32;
+                Self {
+                    instructions_per_second: info
+                        .cycles_per_frame
+                        .map_or(default.instructions_per_second, per_second),
+                    quirks: info.quirks(),
+                    rom: Some(info),
+                    ..default
+                }
+            }
+            None => Self {
+                quirks: Analysis::of(rom).suggest().quirks,
+                ..Self::default(false)
+            },
         }
     }
 }
@@ -0,0 +1,122 @@
+// Rectangles kept apart before they are folded into one.
+const MAX_RECTS: usize = 8;
+
+/// An area of the display, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Whether the pixel is inside.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    /// The smallest rectangle covering both.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    // Whether the two overlap or share an edge.
+    fn touches(&self, other: &Rect) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+}
+
+/// The parts of the display that changed since it was last handed to the IO.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Damage {
+    rects: [Rect; MAX_RECTS],
+    len: usize,
+}
+
+impl Damage {
+    /// Mark an area as changed, merging it with any area it touches.
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        let mut rect = rect;
+        // Merging can make a rectangle touch ones it missed before.
+        while let Some(i) = self.rects().iter().position(|r| r.touches(&rect)) {
+            rect = rect.union(&self.rects[i]);
+            self.len -= 1;
+            self.rects[i] = self.rects[self.len];
+        }
+        if self.len == MAX_RECTS {
+            rect = self.rects().iter().fold(rect, |all, r| all.union(r));
+            self.len = 0;
+        }
+        self.rects[self.len] = rect;
+        self.len += 1;
+    }
+
+    /// The changed areas. They never overlap.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forget every change.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_touching_areas() {
+        let mut damage = Damage::default();
+        damage.add(Rect::new(0, 0, 8, 5));
+        damage.add(Rect::new(20, 20, 8, 5));
+        damage.add(Rect::new(8, 0, 8, 5));
+        damage.add(Rect::new(0, 0, 0, 5));
+        assert_eq!(
+            damage.rects(),
+            [Rect::new(20, 20, 8, 5), Rect::new(0, 0, 16, 5)]
+        );
+        damage.add(Rect::new(10, 4, 12, 17));
+        assert_eq!(damage.rects(), [Rect::new(0, 0, 28, 25)]);
+    }
+
+    #[test]
+    fn folds_into_one_area_when_full() {
+        let mut damage = Damage::default();
+        for i in 0..=MAX_RECTS {
+            damage.add(Rect::new(i * 4, i * 4, 1, 1));
+        }
+        assert_eq!(damage.rects(), [Rect::new(0, 0, 33, 33)]);
+        assert!(damage.rects()[0].contains(32, 32));
+        damage.clear();
+        assert!(damage.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{Blend, Platform, Quirks};
+use alloc::string::ToString;
+use roms::ROMS;
+use sha1_smol::Sha1;
+
+// Generated, see examples/database.rs.
+#[rustfmt::skip]
+mod roms;
+
+/// What is known about a ROM, looked up by the SHA-1 of its bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomInfo {
+    pub sha1: &'static str,
+    pub title: &'static str,
+    pub author: Option<&'static str>,
+    pub platform: Platform,
+    /// The quirks the game needs, if they differ from its platform's.
+    pub quirks: Option<Quirks>,
+    /// Instructions per timer tick the game is meant to run at.
+    pub cycles_per_frame: Option<usize>,
+    /// CHIP-8 keys the game uses, by what they do, such as `"up"` or
+    /// `"player2Up"`.
+    pub keys: &'static [(&'static str, u8)],
+    /// Host keys to bind on top of the user's keymap, see `Keymap`.
+    pub bindings: &'static [(&'static str, u8)],
+    /// Background and foreground colour as `0xRRGGBB`, if the game wants its own.
+    pub colors: Option<[u32; 2]>,
+    /// Anti-flicker blending that suits the game.
+    pub blend: Blend,
+}
+
+impl RomInfo {
+    /// Look up a ROM in the bundled database. Out of the box that only
+    /// knows the ROMs in the repository's roms/ directory; see
+    /// examples/database.rs for bundling the community CHIP-8 database.
+    pub fn find(rom: &[u8]) -> Option<&'static RomInfo> {
+        let hash = Sha1::from(rom).digest().to_string();
+        let i = ROMS.binary_search_by(|info| info.sha1.cmp(&hash)).ok()?;
+        Some(&ROMS[i])
+    }
+
+    /// The quirks to run the game with.
+    pub fn quirks(&self) -> Quirks {
+        self.quirks.unwrap_or(Quirks::for_platform(self.platform))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChipConfig;
+
+    #[test]
+    fn finds_bundled_roms() {
+        let pong = include_bytes!("../roms/PONG");
+        let info = RomInfo::find(pong).unwrap();
+        assert_eq!(info.title, "Pong");
+        let config = ChipConfig::for_rom(pong);
+        assert_eq!(config.quirks, Quirks::COSMAC);
+        assert_eq!(config.instructions_per_second, 8 * config.timer_hz as u32);
+        assert_eq!(config.rom, Some(info));
+    }
+
+    #[test]
+    fn entries_are_sorted_and_consistent() {
+        assert!(ROMS.windows(2).all(|pair| pair[0].sha1 < pair[1].sha1));
+        for info in ROMS {
+            let platform = Quirks::for_platform(info.platform);
+            // Only what differs from the platform is listed, and the
+            // database has no switch for `key_release`.
+            assert_ne!(info.quirks, Some(platform), "{}", info.title);
+            assert_eq!(
+                info.quirks().key_release,
+                platform.key_release,
+                "{}",
+                info.title
+            );
+            for &(name, key) in info.keys.iter().chain(info.bindings) {
+                assert!(key < 16, "{}: {}", info.title, name);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_roms_are_analysed() {
+        assert!(RomInfo::find(&[0x12, 0x00]).is_none());
+        assert_eq!(ChipConfig::for_rom(&[0x12, 0x00]).quirks, Quirks::COSMAC);
+        assert_eq!(ChipConfig::for_rom(&[0x00, 0xFF]).quirks, Quirks::SCHIP);
+    }
+}
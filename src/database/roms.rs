@@ -0,0 +1,57 @@
+// Generated by `cargo run --example database`. Do not edit; regenerate it
+// instead.
+
+use super::RomInfo;
+use crate::{Blend, Platform};
+
+// Sorted by SHA-1.
+pub(super) static ROMS: &[RomInfo] = &[
+    RomInfo {
+        sha1: "1ba58656810b67fd131eb9af3e3987863bf26c90",
+        title: "IBM Logo",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        cycles_per_frame: Some(8),
+        keys: &[],
+        bindings: &[],
+        colors: None,
+        blend: Blend::None,
+    },
+    RomInfo {
+        sha1: "9df1689015a0d1d95144f141903296f9f1c35fc5",
+        title: "BC Test",
+        author: Some("BestCoder"),
+        platform: Platform::Chip8,
+        quirks: None,
+        cycles_per_frame: Some(15),
+        keys: &[],
+        bindings: &[],
+        colors: None,
+        blend: Blend::None,
+    },
+    RomInfo {
+        sha1: "b232ef880bd6060fb45fa6effed7edf0ae95670e",
+        title: "Pong",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        cycles_per_frame: Some(8),
+        keys: &[("down", 4), ("player2Down", 13), ("player2Up", 12), ("up", 1)],
+        bindings: &[("down", 4), ("up", 1)],
+        colors: None,
+        blend: Blend::Or(2),
+    },
+    RomInfo {
+        sha1: "f1cfcffe1937ed6dd6eeed1a7f85dfc777bda700",
+        title: "Test Opcode",
+        author: Some("corax89"),
+        platform: Platform::Chip8,
+        quirks: None,
+        cycles_per_frame: Some(15),
+        keys: &[],
+        bindings: &[],
+        colors: None,
+        blend: Blend::None,
+    },
+];
@@ -0,0 +1,94 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// Everything that can go wrong running a program, or in a backend.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The program does not fit in memory, with its size in bytes.
+    ProgramTooLarge(usize),
+    /// An opcode that is not a CHIP-8 instruction.
+    InvalidOpcode(u16),
+    /// The program counter ran off the end of memory.
+    CounterOutOfBounds(usize),
+    /// An instruction read or wrote `len` bytes at `address`, past the end
+    /// of memory.
+    MemoryOutOfBounds { address: u16, len: usize },
+    /// A subroutine call with the stack already full.
+    StackOverflow,
+    /// A return with nothing on the stack.
+    StackUnderflow,
+    /// A key outside the keypad.
+    InvalidKey(usize),
+    /// Text that could not be parsed, such as a keymap.
+    Parse(String),
+    /// An I/O error, usually from a backend.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// Anything else, described. Backends without `std` report their
+    /// errors with this.
+    Other(String),
+}
+
+impl Error {
+    /// An `Other` error, described by `error`.
+    pub fn other(error: impl fmt::Display) -> Self {
+        Error::Other(error.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ProgramTooLarge(size) => write!(f, "Program too large: {} bytes", size),
+            Error::InvalidOpcode(opcode) => {
+                write!(f, "Invalid or Unimplemented Instruction: {:04x}", opcode)
+            }
+            Error::CounterOutOfBounds(address) => {
+                write!(f, "Program counter out of bounds: {:#05x}", address)
+            }
+            Error::MemoryOutOfBounds { address, len } => write!(
+                f,
+                "Memory access out of bounds: {} bytes at {:#05x}",
+                len, address
+            ),
+            Error::StackOverflow => f.write_str("Stack overflow"),
+            Error::StackUnderflow => f.write_str("Return with an empty stack"),
+            Error::InvalidKey(key) => write!(f, "Invalid key: {}", key),
+            Error::Parse(message) | Error::Other(message) => f.write_str(message),
+            #[cfg(feature = "std")]
+            Error::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            Error::ProgramTooLarge(_) | Error::InvalidKey(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, error)
+            }
+            Error::Parse(_) => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+            error => std::io::Error::other(error),
+        }
+    }
+}
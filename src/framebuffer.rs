@@ -0,0 +1,192 @@
+// Largest display any supported platform has, and the most bit planes.
+const MAX_WIDTH: usize = 128;
+const MAX_HEIGHT: usize = 64;
+const MAX_PLANES: usize = 2;
+const PLANE_BYTES: usize = MAX_WIDTH * MAX_HEIGHT / 8;
+
+/// Display sizes used by CHIP-8 and its extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    /// 64x32, the original display.
+    #[default]
+    Low,
+    /// 64x64, the two page hires mode of the COSMAC VIP.
+    Tall,
+    /// 128x64, SUPER-CHIP and XO-CHIP hires.
+    High,
+}
+
+impl Resolution {
+    pub fn width(&self) -> usize {
+        match self {
+            Resolution::Low | Resolution::Tall => 64,
+            Resolution::High => 128,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        match self {
+            Resolution::Low => 32,
+            Resolution::Tall | Resolution::High => 64,
+        }
+    }
+}
+
+/// The display, as one or more bit planes.
+///
+/// Each plane is packed row-major, eight pixels to a byte with the leftmost
+/// pixel in the top bit, the same layout as sprite data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framebuffer {
+    resolution: Resolution,
+    planes: usize,
+    bits: [[u8; PLANE_BYTES]; MAX_PLANES],
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Self::new(Resolution::Low, 1)
+    }
+}
+
+impl Framebuffer {
+    /// A blank display. `planes` is clamped to between one and two.
+    pub fn new(resolution: Resolution, planes: usize) -> Self {
+        Self {
+            resolution,
+            planes: planes.clamp(1, MAX_PLANES),
+            bits: [[0; PLANE_BYTES]; MAX_PLANES],
+        }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn width(&self) -> usize {
+        self.resolution.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.resolution.height()
+    }
+
+    pub fn planes(&self) -> usize {
+        self.planes
+    }
+
+    /// Switch resolution, which clears the display.
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = resolution;
+        self.clear();
+    }
+
+    /// Turn every pixel off.
+    pub fn clear(&mut self) {
+        self.bits = [[0; PLANE_BYTES]; MAX_PLANES];
+    }
+
+    /// Whether a pixel is lit in any plane. Out of range pixels are off.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixel(x, y) != 0
+    }
+
+    /// The colour index of a pixel, with bit `n` set if plane `n` is lit.
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        (0..self.planes)
+            .filter(|&plane| self.get_plane(plane, x, y))
+            .fold(0, |index, plane| index | 1 << plane)
+    }
+
+    /// Whether a pixel is lit in one plane.
+    pub fn get_plane(&self, plane: usize, x: usize, y: usize) -> bool {
+        match self.locate(x, y) {
+            Some((byte, mask)) if plane < self.planes => self.bits[plane][byte] & mask != 0,
+            _ => false,
+        }
+    }
+
+    /// Light or clear a pixel in one plane. Out of range pixels are ignored.
+    pub fn set(&mut self, plane: usize, x: usize, y: usize, on: bool) {
+        if let Some((byte, mask)) = self.locate(x, y).filter(|_| plane < self.planes) {
+            if on {
+                self.bits[plane][byte] |= mask;
+            } else {
+                self.bits[plane][byte] &= !mask;
+            }
+        }
+    }
+
+    /// XOR a pixel in one plane, returning whether it was lit before.
+    pub fn toggle(&mut self, plane: usize, x: usize, y: usize) -> bool {
+        match self.locate(x, y).filter(|_| plane < self.planes) {
+            Some((byte, mask)) => {
+                let was_on = self.bits[plane][byte] & mask != 0;
+                self.bits[plane][byte] ^= mask;
+                was_on
+            }
+            None => false,
+        }
+    }
+
+    /// One plane in the packed layout, `width / 8` bytes per row.
+    pub fn packed(&self, plane: usize) -> &[u8] {
+        &self.bits[plane.min(self.planes - 1)][..self.width() * self.height() / 8]
+    }
+
+    /// The pixels of one row, left to right, lit in any plane.
+    pub fn row(&self, y: usize) -> impl Iterator<Item = bool> + '_ {
+        (0..self.width()).map(move |x| self.get(x, y))
+    }
+
+    /// Every row, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = bool> + '_> + '_ {
+        (0..self.height()).map(move |y| self.row(y))
+    }
+
+    // The byte and bit mask of a pixel, if it is on screen.
+    fn locate(&self, x: usize, y: usize) -> Option<(usize, u8)> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        Some(((y * self.width() + x) / 8, 0x80 >> (x % 8)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_rows_msb_first() {
+        let mut screen = Framebuffer::default();
+        screen.set(0, 0, 0, true);
+        screen.set(0, 9, 1, true);
+        let packed = screen.packed(0);
+        assert_eq!(packed.len(), 64 * 32 / 8);
+        assert_eq!(packed[0], 0x80);
+        assert_eq!(packed[8 + 1], 0x40);
+        assert!(screen.row(1).nth(9).unwrap());
+    }
+
+    #[test]
+    fn planes_combine_into_colour_indices() {
+        let mut screen = Framebuffer::new(Resolution::High, 2);
+        assert!(!screen.toggle(1, 127, 63));
+        screen.set(0, 127, 63, true);
+        assert_eq!(screen.pixel(127, 63), 0b11);
+        assert!(screen.toggle(0, 127, 63));
+        assert_eq!(screen.pixel(127, 63), 0b10);
+        screen.set(0, 128, 0, true);
+        assert_eq!(screen.rows().flatten().filter(|&on| on).count(), 1);
+    }
+
+    #[test]
+    fn switching_resolution_clears() {
+        let mut screen = Framebuffer::default();
+        screen.set(0, 1, 1, true);
+        screen.set_resolution(Resolution::Tall);
+        assert_eq!((screen.width(), screen.height()), (64, 64));
+        assert!(!screen.get(1, 1));
+    }
+}
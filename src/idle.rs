@@ -0,0 +1,97 @@
+use crate::{ChipIO, ChipOxide, Instruction, INSTRUCTION_SIZE};
+
+impl<'a, I> ChipOxide<'a, I>
+where
+    I: ChipIO,
+{
+    // Whether the instruction just run at `address` leaves the machine in a
+    // busy-wait that cannot end before the next frame, when the timers tick
+    // and the keypad updates. The rest of the frame can then be skipped.
+    pub(crate) fn idling(&mut self, address: usize) -> bool {
+        if !self.config.skip_idle {
+            return false;
+        }
+        let idle = match self.instruction_at(address) {
+            // Jumping to itself, or FX0A still waiting for a key.
+            Some(Instruction::Jump(_) | Instruction::OffsetJump(..) | Instruction::KeyWait(_)) => {
+                self.counter == address
+            }
+            // FX07; 3X00; 1NNN back to the FX07, with the delay timer running.
+            Some(Instruction::GetDelay(x)) => {
+                self.delay != 0
+                    && self.instruction_at(address + INSTRUCTION_SIZE)
+                        == Some(Instruction::SkipED(x, 0))
+                    && self.instruction_at(address + 2 * INSTRUCTION_SIZE)
+                        == Some(Instruction::Jump(address as u16))
+            }
+            _ => false,
+        };
+        if idle {
+            self.idle_frames += 1;
+        }
+        idle
+    }
+
+    // The instruction at an address, from the decode cache where it can be.
+    // This runs after every instruction, so it must not decode each time.
+    pub(crate) fn instruction_at(&mut self, address: usize) -> Option<Instruction> {
+        if let Some(inst) = self.decoded.get(address) {
+            return Some(inst);
+        }
+        let bytes = self.memory.get(address..address + INSTRUCTION_SIZE)?;
+        let inst = Instruction::try_from((bytes[0] as u16) << 8 | bytes[1] as u16).ok()?;
+        self.decoded.insert(address, inst);
+        Some(inst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChipConfig, ChipOxide, Instruction, MockIO};
+
+    // Run a program for some frames, returning how many frames were cut short.
+    fn idle_frames(program: &[u8], skip_idle: bool, frames: usize) -> u64 {
+        let config = ChipConfig {
+            skip_idle,
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(program, &mut io, &config).unwrap();
+        for _ in 0..frames {
+            chip8.cycle().unwrap();
+        }
+        chip8.idle_frames()
+    }
+
+    #[test]
+    fn detects_self_jumps_and_key_waits() {
+        assert_eq!(idle_frames(&[0x12, 0x00], true, 3), 3);
+        assert_eq!(idle_frames(&[0xF0, 0x0A], true, 3), 3);
+        assert_eq!(idle_frames(&[0x12, 0x00], false, 3), 0);
+        // A loop through two instructions is not recognised.
+        assert_eq!(idle_frames(&[0x60, 0x00, 0x12, 0x00], true, 3), 0);
+    }
+
+    #[test]
+    fn looks_instructions_up_in_the_decode_cache() {
+        let config = ChipConfig::default(false);
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&[0x12, 0x00], &mut io, &config).unwrap();
+        assert_eq!(chip8.instruction_at(0x200), Some(Instruction::Jump(0x200)));
+        assert_eq!(chip8.decoded.get(0x200), Some(Instruction::Jump(0x200)));
+        // What is cached is used, without decoding memory again.
+        chip8.decoded.insert(0x200, Instruction::Clear);
+        assert_eq!(chip8.instruction_at(0x200), Some(Instruction::Clear));
+    }
+
+    #[test]
+    fn detects_delay_timer_waits() {
+        // Delay = 3, then wait for it: FX07; 3X00; 1NNN.
+        let program = [
+            0x60, 0x03, 0xF0, 0x15, 0xF1, 0x07, 0x31, 0x00, 0x12, 0x04, 0x12, 0x0A,
+        ];
+        // Three frames of waiting, then the spin at the end.
+        assert_eq!(idle_frames(&program, true, 4), 4);
+        assert_eq!(idle_frames(&program, false, 4), 0);
+    }
+}
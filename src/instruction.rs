@@ -1,9 +1,13 @@
-use std::io::{Error, ErrorKind};
+use crate::Error;
 
-#[derive(Debug)]
+/// A decoded CHIP-8 instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     Clear,
     Return,
+    Exit,
+    LowRes,
+    HighRes,
     Jump(u16),
     SubRoutine(u16),
     SkipED(u8, u8),       // Equal to Data
@@ -53,6 +57,9 @@ impl TryFrom<u16> for Instruction {
         match (inst, r0, r1, n) {
             (0, 0, 0xE, 0) => Ok(Instruction::Clear),
             (0, 0, 0xE, 0xE) => Ok(Instruction::Return),
+            (0, 0, 0xF, 0xD) => Ok(Instruction::Exit),
+            (0, 0, 0xF, 0xE) => Ok(Instruction::LowRes),
+            (0, 0, 0xF, 0xF) => Ok(Instruction::HighRes),
             (1, _, _, _) => Ok(Instruction::Jump(nnn)),
             (2, _, _, _) => Ok(Instruction::SubRoutine(nnn)),
             (3, _, _, _) => Ok(Instruction::SkipED(r0, nn)),
@@ -73,7 +80,7 @@ impl TryFrom<u16> for Instruction {
             (0xA, _, _, _) => Ok(Instruction::SetIndex(nnn)),
             (0xB, _, _, _) => Ok(Instruction::OffsetJump(r0, nnn)),
             (0xC, _, _, _) => Ok(Instruction::Random(r0, nn)),
-            (0xD, _, _, _) => Ok(Instruction::Draw(r0, r1 as u8, n)),
+            (0xD, _, _, _) => Ok(Instruction::Draw(r0, r1, n)),
             (0xE, _, 9, 0xE) => Ok(Instruction::KeyPressed(r0)),
             (0xE, _, 0xA, 1) => Ok(Instruction::KeyReleased(r0)),
             (0xF, _, 0, 7) => Ok(Instruction::GetDelay(r0)),
@@ -85,10 +92,7 @@ impl TryFrom<u16> for Instruction {
             (0xF, _, 3, 3) => Ok(Instruction::AsDecimal(r0)),
             (0xF, _, 5, 5) => Ok(Instruction::Save(r0)),
             (0xF, _, 6, 5) => Ok(Instruction::Load(r0)),
-            _ => Err(Error::new(
-                ErrorKind::Other,
-                format!("Invalid or Unimplemented Instruction: {:016x}", value),
-            )),
+            _ => Err(Error::InvalidOpcode(value)),
         }
     }
 }
@@ -1,18 +1,52 @@
-use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
-use std::io::Error;
+use crate::{Error, Framebuffer, Keypad, Rect};
+
+/// Requests from the backend to the running emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Run at a percentage of normal speed: above 100 to fast-forward,
+    /// below for slow motion.
+    Speed(u32),
+    Pause,
+    Resume,
+    TogglePause,
+    /// Run a single frame, pausing first if running.
+    Step,
+    /// Restart the program without restarting the emulator.
+    Reset,
+    /// Stop running, as the SUPER-CHIP 00FD instruction does.
+    Exit,
+}
 
 /// Trait for IO.
 pub trait ChipIO {
-    /// Update the screen
-    fn update_screen(
-        &mut self,
-        screen: &[[bool; SCREEN_HEIGHT]; SCREEN_WIDTH],
-    ) -> Result<(), Error>;
+    /// Show a finished frame. Called once per timer tick, and after every
+    /// draw if the config asks for it. `damage` lists the areas that changed
+    /// since the last frame; pixels outside them are as they were.
+    fn present_frame(&mut self, screen: &Framebuffer, damage: &[Rect]) -> Result<(), Error>;
 
     /// Toggle Sound
     fn start_beep(&mut self) -> Result<(), Error>;
     fn end_beep(&mut self) -> Result<(), Error>;
 
-    /// Get keyboard State
-    fn get_key(&mut self) -> Result<Option<(usize, bool)>, Error>;
+    /// Bring the keypad up to date. Called once per timer tick, before any
+    /// instruction of that tick runs. By default every pending `get_key`
+    /// event is applied in order; backends that can read every key at once
+    /// should override this and use `Keypad::set_all` instead.
+    fn update_keypad(&mut self, keypad: &mut Keypad) -> Result<(), Error> {
+        while let Some((key, down)) = self.get_key()? {
+            keypad.set(key, down)?;
+        }
+        Ok(())
+    }
+
+    /// Get the next pending command, if any. Polled between frames, and
+    /// while paused.
+    fn next_command(&mut self) -> Result<Option<Command>, Error> {
+        Ok(None)
+    }
+
+    /// Get the next pending key change, if any.
+    fn get_key(&mut self) -> Result<Option<(usize, bool)>, Error> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,789 @@
+use crate::{
+    recompile::ends_block, ChipIO, ChipOxide, Error, Instruction, Quirks, INSTRUCTION_SIZE,
+    MEM_SIZE, VF,
+};
+use cranelift_codegen::{
+    ir::{condcodes::IntCC, types, AbiParam, InstBuilder, MemFlags, Type, Value},
+    settings::{self, Configurable},
+    Context,
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Module};
+use std::{mem::offset_of, ops::Range};
+
+// Times a block has to be reached before it is compiled.
+const HOT: u8 = 8;
+// The most instructions compiled into one block.
+const MAX_BLOCK: usize = 64;
+// Times the code at an address may be overwritten before it is left to the
+// interpreter for good.
+const MAX_INVALIDATIONS: u8 = 4;
+// Blocks compiled before all code is thrown away, so programs that keep
+// rewriting themselves do not use ever more memory.
+const MAX_COMPILED: usize = 4096;
+
+// A compiled block, called with the machine and the most instructions it
+// may run, which only blocks that loop back to their start need. Returns the
+// instructions it ran, or `FAULT` if one failed and left its error in
+// `Jit::fault`.
+type Run = unsafe extern "C" fn(*mut u8, u32) -> u32;
+const FAULT: u32 = u32::MAX;
+// Runs one instruction for compiled code, as `execute_at` would.
+type Helper = unsafe extern "C" fn(*mut u8, u32, u32) -> u32;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Native {
+    run: Run,
+    len: usize,
+}
+
+// Where compiled code finds the parts of the machine it uses directly.
+struct Layout {
+    register: i32,
+    index: i32,
+    counter: i32,
+    delay: i32,
+    helper: Helper,
+    quirks: Quirks,
+}
+
+/// Native code for the blocks of one machine, compiled as they get hot.
+pub(crate) struct Jit {
+    module: Option<JITModule>,
+    context: Context,
+    builder: FunctionBuilderContext,
+    blocks: Vec<Option<Native>>,
+    runs: Vec<u8>,
+    invalidations: Vec<u8>,
+    compiled: usize,
+    fault: Option<Error>,
+}
+
+impl Jit {
+    pub(crate) fn new() -> Result<Self, Error> {
+        let module = new_module()?;
+        Ok(Self {
+            context: module.make_context(),
+            module: Some(module),
+            builder: FunctionBuilderContext::new(),
+            blocks: vec![None; MEM_SIZE],
+            runs: vec![0; MEM_SIZE],
+            invalidations: vec![0; MEM_SIZE],
+            compiled: 0,
+            fault: None,
+        })
+    }
+
+    // Throw away blocks overlapping memory that has been written.
+    pub(crate) fn invalidate(&mut self, written: Range<usize>) {
+        let first = written
+            .start
+            .saturating_sub((MAX_BLOCK - 1) * INSTRUCTION_SIZE);
+        for address in first..written.end.min(MEM_SIZE) {
+            let Some(block) = self.blocks[address] else {
+                continue;
+            };
+            if address + block.len * INSTRUCTION_SIZE > written.start {
+                self.blocks[address] = None;
+                self.runs[address] = 0;
+                self.invalidations[address] = self.invalidations[address].saturating_add(1);
+            }
+        }
+    }
+
+    // Count a run of the code at an address, returning whether it should
+    // be compiled now.
+    fn heat(&mut self, address: usize) -> bool {
+        let Some(runs) = self.runs.get_mut(address) else {
+            return false;
+        };
+        *runs = runs.saturating_add(1);
+        *runs >= HOT && self.invalidations[address] < MAX_INVALIDATIONS
+    }
+
+    fn compile(
+        &mut self,
+        address: usize,
+        insts: &[Instruction],
+        loops: bool,
+        layout: &Layout,
+    ) -> Result<Native, Error> {
+        if self.compiled == MAX_COMPILED {
+            self.flush()?;
+        }
+        let module = self.module.as_mut().expect("JIT module");
+        let pointer = module.target_config().pointer_type();
+        let context = &mut self.context;
+        context.func.signature.params.push(AbiParam::new(pointer));
+        context
+            .func
+            .signature
+            .params
+            .push(AbiParam::new(types::I32));
+        context
+            .func
+            .signature
+            .returns
+            .push(AbiParam::new(types::I32));
+        let mut helper = module.make_signature();
+        helper.params.push(AbiParam::new(pointer));
+        helper.params.extend([AbiParam::new(types::I32); 2]);
+        helper.returns.push(AbiParam::new(types::I32));
+
+        let mut b = FunctionBuilder::new(&mut context.func, &mut self.builder);
+        let helper = b.import_signature(helper);
+        let entry = b.create_block();
+        let body = b.create_block();
+        let fail = b.create_block();
+        b.append_block_params_for_function_params(entry);
+        b.switch_to_block(entry);
+        b.seal_block(entry);
+        let (machine, limit) = (b.block_params(entry)[0], b.block_params(entry)[1]);
+        // Instructions run by earlier times round, and skipped this time.
+        let (ran, skipped) = (Variable::from_u32(0), Variable::from_u32(1));
+        b.declare_var(ran, types::I32);
+        b.declare_var(skipped, types::I32);
+        let zero = b.ins().iconst(types::I32, 0);
+        b.def_var(ran, zero);
+        b.ins().jump(body, &[]);
+        b.switch_to_block(body);
+        b.def_var(skipped, zero);
+        let mut emit = Emit {
+            b,
+            machine,
+            pointer,
+            layout,
+        };
+        // Where skips inside the block land, up to just past its end.
+        let mut targets = vec![None; insts.len() + 1];
+        for (i, &inst) in insts.iter().enumerate() {
+            let at = address + i * INSTRUCTION_SIZE;
+            let last = i + 1 == insts.len();
+            let b = &mut emit.b;
+            if let Some(target) = targets[i] {
+                b.ins().jump(target, &[]);
+                b.switch_to_block(target);
+                b.seal_block(target);
+            }
+            if !last {
+                if let Some(cond) = emit.skip_cond(inst) {
+                    let b = &mut emit.b;
+                    let (taken, next) = (b.create_block(), b.create_block());
+                    let target = *targets[i + 2].get_or_insert_with(|| b.create_block());
+                    b.ins().brif(cond, taken, &[], next, &[]);
+                    b.switch_to_block(taken);
+                    b.seal_block(taken);
+                    let count = b.use_var(skipped);
+                    let count = b.ins().iadd_imm(count, 1);
+                    b.def_var(skipped, count);
+                    b.ins().jump(target, &[]);
+                    b.switch_to_block(next);
+                    b.seal_block(next);
+                    continue;
+                }
+            }
+            if emit.native(at, inst, last) {
+                continue;
+            }
+            // Anything else goes through the interpreter, which also sets
+            // the program counter.
+            let b = &mut emit.b;
+            let callee = b.ins().iconst(pointer, layout.helper as usize as i64);
+            let at = b.ins().iconst(types::I32, at as i64);
+            let opcode = b.ins().iconst(types::I32, opcode(inst) as i64);
+            let call = b
+                .ins()
+                .call_indirect(helper, callee, &[machine, at, opcode]);
+            let status = b.inst_results(call)[0];
+            let next = b.create_block();
+            b.ins().brif(status, fail, &[], next, &[]);
+            b.switch_to_block(next);
+            b.seal_block(next);
+        }
+        let mut b = emit.b;
+        let exit = b.create_block();
+        b.append_block_param(exit, types::I32);
+        // The instructions run so far, counting this time round.
+        let total = |b: &mut FunctionBuilder| {
+            let (before, count) = (b.use_var(ran), b.use_var(skipped));
+            let this_time = b.ins().irsub_imm(count, insts.len() as i64);
+            b.ins().iadd(before, this_time)
+        };
+        let all = total(&mut b);
+        if loops {
+            // Go round again while the whole block still fits.
+            let left = b.ins().isub(limit, all);
+            let fits =
+                b.ins()
+                    .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, left, insts.len() as i64);
+            b.def_var(ran, all);
+            b.ins().brif(fits, body, &[], exit, &[all]);
+        } else {
+            b.ins().jump(exit, &[all]);
+        }
+        if let Some(end) = targets[insts.len()] {
+            // Skipped past the last instruction, which may have set the
+            // counter otherwise.
+            b.switch_to_block(end);
+            b.seal_block(end);
+            let next = b
+                .ins()
+                .iconst(pointer, (address + insts.len() * INSTRUCTION_SIZE) as i64);
+            b.ins()
+                .store(MemFlags::trusted(), next, machine, layout.counter);
+            let all = total(&mut b);
+            b.ins().jump(exit, &[all]);
+        }
+        b.seal_block(body);
+        b.switch_to_block(exit);
+        b.seal_block(exit);
+        let all = b.block_params(exit)[0];
+        b.ins().return_(&[all]);
+        b.switch_to_block(fail);
+        b.seal_block(fail);
+        let fault = b.ins().iconst(types::I32, FAULT as i64);
+        b.ins().return_(&[fault]);
+        b.finalize();
+
+        let defined = module
+            .declare_anonymous_function(&context.func.signature)
+            .map_err(Error::other)
+            .and_then(|id| {
+                let defined = module.define_function(id, context);
+                defined.map(|()| id).map_err(Error::other)
+            });
+        // Ready the context for the next block, even if this one failed.
+        module.clear_context(context);
+        let id = defined?;
+        module.finalize_definitions().map_err(Error::other)?;
+        // SAFETY: the code was built with exactly this signature, and lives
+        // as long as the module, which outlives every copy of the pointer.
+        let run =
+            unsafe { std::mem::transmute::<*const u8, Run>(module.get_finalized_function(id)) };
+        let block = Native {
+            run,
+            len: insts.len(),
+        };
+        self.blocks[address] = Some(block);
+        self.compiled += 1;
+        Ok(block)
+    }
+
+    // Free all compiled code and start over.
+    fn flush(&mut self) -> Result<(), Error> {
+        self.blocks.fill(None);
+        self.compiled = 0;
+        if let Some(module) = self.module.replace(new_module()?) {
+            // SAFETY: no compiled code is running, and the pointers to it
+            // were all just dropped.
+            unsafe { module.free_memory() };
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Jit {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // SAFETY: as in `flush`; the blocks go with `self`.
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+fn new_module() -> Result<JITModule, Error> {
+    let mut flags = settings::builder();
+    flags.set("opt_level", "speed").map_err(Error::other)?;
+    let isa = cranelift_native::builder()
+        .map_err(Error::other)?
+        .finish(settings::Flags::new(flags))
+        .map_err(Error::other)?;
+    Ok(JITModule::new(JITBuilder::with_isa(
+        isa,
+        default_libcall_names(),
+    )))
+}
+
+// Builds the native code for one block.
+struct Emit<'b, 'l> {
+    b: FunctionBuilder<'b>,
+    machine: Value,
+    pointer: Type,
+    layout: &'l Layout,
+}
+
+impl Emit<'_, '_> {
+    fn load(&mut self, ty: Type, offset: i32) -> Value {
+        self.b
+            .ins()
+            .load(ty, MemFlags::trusted(), self.machine, offset)
+    }
+
+    fn store(&mut self, value: Value, offset: i32) {
+        self.b
+            .ins()
+            .store(MemFlags::trusted(), value, self.machine, offset);
+    }
+
+    fn get(&mut self, register: u8) -> Value {
+        self.load(types::I8, self.layout.register + register as i32)
+    }
+
+    fn set(&mut self, register: u8, value: Value) {
+        self.store(value, self.layout.register + register as i32);
+    }
+
+    fn set_flag(&mut self, value: Value) {
+        self.set(VF as u8, value);
+    }
+
+    fn byte(&mut self, value: u8) -> Value {
+        self.b.ins().iconst(types::I8, value as i64)
+    }
+
+    fn jump_to(&mut self, target: Value) {
+        self.store(target, self.layout.counter);
+    }
+
+    // Whether a skip on registers skips, or `None` for other instructions.
+    fn skip_cond(&mut self, inst: Instruction) -> Option<Value> {
+        let (cc, vx, operand) = match inst {
+            Instruction::SkipED(x, nn) => (IntCC::Equal, self.get(x), self.byte(nn)),
+            Instruction::SkipNED(x, nn) => (IntCC::NotEqual, self.get(x), self.byte(nn)),
+            Instruction::SkipER(x, y) => (IntCC::Equal, self.get(x), self.get(y)),
+            Instruction::SkipNER(x, y) => (IntCC::NotEqual, self.get(x), self.get(y)),
+            _ => return None,
+        };
+        Some(self.b.ins().icmp(cc, vx, operand))
+    }
+
+    // Skip the next instruction if `cond` is set.
+    fn skip_if(&mut self, address: usize, cond: Value) {
+        let next = self
+            .b
+            .ins()
+            .iconst(self.pointer, (address + INSTRUCTION_SIZE) as i64);
+        let skip = self
+            .b
+            .ins()
+            .iconst(self.pointer, (address + 2 * INSTRUCTION_SIZE) as i64);
+        let target = self.b.ins().select(cond, skip, next);
+        self.jump_to(target);
+    }
+
+    // Emit native code for an instruction if it has some, returning whether
+    // it did. Mirrors `opcodes.rs`.
+    fn native(&mut self, address: usize, inst: Instruction, last: bool) -> bool {
+        let quirks = self.layout.quirks;
+        match inst {
+            Instruction::Jump(nnn) => {
+                let target = self.b.ins().iconst(self.pointer, nnn as i64);
+                self.jump_to(target);
+                return true;
+            }
+            Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..) => {
+                let cond = self.skip_cond(inst).expect("skip");
+                self.skip_if(address, cond);
+                return true;
+            }
+            Instruction::SetRegisterD(x, nn) => {
+                let nn = self.byte(nn);
+                self.set(x, nn);
+            }
+            Instruction::AddRegisterD(x, nn) => {
+                let (vx, nn) = (self.get(x), self.byte(nn));
+                let sum = self.b.ins().iadd(vx, nn);
+                self.set(x, sum);
+            }
+            Instruction::SetRegisterR(x, y) => {
+                let vy = self.get(y);
+                self.set(x, vy);
+            }
+            Instruction::BinaryOR(x, y)
+            | Instruction::BinaryAND(x, y)
+            | Instruction::LogicalXOR(x, y) => {
+                let (vx, vy) = (self.get(x), self.get(y));
+                let result = match inst {
+                    Instruction::BinaryOR(..) => self.b.ins().bor(vx, vy),
+                    Instruction::BinaryAND(..) => self.b.ins().band(vx, vy),
+                    _ => self.b.ins().bxor(vx, vy),
+                };
+                self.set(x, result);
+                if quirks.vf_reset {
+                    let zero = self.byte(0);
+                    self.set_flag(zero);
+                }
+            }
+            Instruction::AddRegisterR(x, y) => {
+                let (vx, vy) = (self.get(x), self.get(y));
+                let sum = self.b.ins().iadd(vx, vy);
+                let carry = self.b.ins().icmp(IntCC::UnsignedLessThan, sum, vx);
+                self.set(x, sum);
+                self.set_flag(carry);
+            }
+            Instruction::SubtractXY(x, y) | Instruction::SubtractYX(x, y) => {
+                let (vx, vy) = (self.get(x), self.get(y));
+                let (a, b) = match inst {
+                    Instruction::SubtractXY(..) => (vx, vy),
+                    _ => (vy, vx),
+                };
+                let difference = self.b.ins().isub(a, b);
+                let no_borrow = self.b.ins().icmp(IntCC::UnsignedGreaterThanOrEqual, a, b);
+                self.set(x, difference);
+                self.set_flag(no_borrow);
+            }
+            Instruction::ShiftRight(x, y) | Instruction::ShiftLeft(x, y) => {
+                let source = self.get(if quirks.shift_vy { y } else { x });
+                let (result, flag) = match inst {
+                    Instruction::ShiftRight(..) => (
+                        self.b.ins().ushr_imm(source, 1),
+                        self.b.ins().band_imm(source, 1),
+                    ),
+                    _ => (
+                        self.b.ins().ishl_imm(source, 1),
+                        self.b.ins().ushr_imm(source, 7),
+                    ),
+                };
+                self.set(x, result);
+                self.set_flag(flag);
+            }
+            Instruction::SetIndex(nnn) => {
+                let nnn = self.b.ins().iconst(types::I16, nnn as i64);
+                self.store(nnn, self.layout.index);
+            }
+            Instruction::AddIndex(x) => {
+                let index = self.load(types::I16, self.layout.index);
+                let vx = self.get(x);
+                let vx = self.b.ins().uextend(types::I16, vx);
+                let sum = self.b.ins().iadd(index, vx);
+                self.store(sum, self.layout.index);
+            }
+            Instruction::GetDelay(x) => {
+                let delay = self.load(types::I8, self.layout.delay);
+                self.set(x, delay);
+            }
+            _ => return false,
+        }
+        // Straight-line code only needs the counter at the end of the block.
+        if last {
+            let next = self
+                .b
+                .ins()
+                .iconst(self.pointer, (address + INSTRUCTION_SIZE) as i64);
+            self.jump_to(next);
+        }
+        true
+    }
+}
+
+// Whether a compiled block has to end after an instruction. Unlike
+// `ends_block`, skips on registers branch inside the block, and FX07 only
+// ends one for the idle check to see it.
+fn ends_native_block(inst: &Instruction, skip_idle: bool) -> bool {
+    match inst {
+        _ if is_skip(inst) => false,
+        Instruction::GetDelay(_) => skip_idle,
+        _ => ends_block(inst),
+    }
+}
+
+fn is_skip(inst: &Instruction) -> bool {
+    matches!(
+        inst,
+        Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..)
+    )
+}
+
+// The opcode an instruction was decoded from.
+fn opcode(inst: Instruction) -> u16 {
+    let xy = |x: u8, y: u8| (x as u16) << 8 | (y as u16) << 4;
+    match inst {
+        Instruction::Clear => 0x00E0,
+        Instruction::Return => 0x00EE,
+        Instruction::Exit => 0x00FD,
+        Instruction::LowRes => 0x00FE,
+        Instruction::HighRes => 0x00FF,
+        Instruction::Jump(nnn) => 0x1000 | nnn,
+        Instruction::SubRoutine(nnn) => 0x2000 | nnn,
+        Instruction::SkipED(x, nn) => 0x3000 | xy(x, 0) | nn as u16,
+        Instruction::SkipNED(x, nn) => 0x4000 | xy(x, 0) | nn as u16,
+        Instruction::SkipER(x, y) => 0x5000 | xy(x, y),
+        Instruction::SetRegisterD(x, nn) => 0x6000 | xy(x, 0) | nn as u16,
+        Instruction::AddRegisterD(x, nn) => 0x7000 | xy(x, 0) | nn as u16,
+        Instruction::SetRegisterR(x, y) => 0x8000 | xy(x, y),
+        Instruction::BinaryOR(x, y) => 0x8001 | xy(x, y),
+        Instruction::BinaryAND(x, y) => 0x8002 | xy(x, y),
+        Instruction::LogicalXOR(x, y) => 0x8003 | xy(x, y),
+        Instruction::AddRegisterR(x, y) => 0x8004 | xy(x, y),
+        Instruction::SubtractXY(x, y) => 0x8005 | xy(x, y),
+        Instruction::ShiftRight(x, y) => 0x8006 | xy(x, y),
+        Instruction::SubtractYX(x, y) => 0x8007 | xy(x, y),
+        Instruction::ShiftLeft(x, y) => 0x800E | xy(x, y),
+        Instruction::SkipNER(x, y) => 0x9000 | xy(x, y),
+        Instruction::SetIndex(nnn) => 0xA000 | nnn,
+        Instruction::OffsetJump(x, nnn) => 0xB000 | xy(x, 0) | (nnn & 0xFF),
+        Instruction::Random(x, nn) => 0xC000 | xy(x, 0) | nn as u16,
+        Instruction::Draw(x, y, n) => 0xD000 | xy(x, y) | n as u16,
+        Instruction::KeyPressed(x) => 0xE09E | xy(x, 0),
+        Instruction::KeyReleased(x) => 0xE0A1 | xy(x, 0),
+        Instruction::GetDelay(x) => 0xF007 | xy(x, 0),
+        Instruction::KeyWait(x) => 0xF00A | xy(x, 0),
+        Instruction::SetDelay(x) => 0xF015 | xy(x, 0),
+        Instruction::SetSound(x) => 0xF018 | xy(x, 0),
+        Instruction::AddIndex(x) => 0xF01E | xy(x, 0),
+        Instruction::GetFont(x) => 0xF029 | xy(x, 0),
+        Instruction::AsDecimal(x) => 0xF033 | xy(x, 0),
+        Instruction::Save(x) => 0xF055 | xy(x, 0),
+        Instruction::Load(x) => 0xF065 | xy(x, 0),
+    }
+}
+
+// Called by compiled code for instructions it has no native code for.
+unsafe extern "C" fn helper<'a, I: ChipIO + 'a>(
+    machine: *mut u8,
+    address: u32,
+    opcode: u32,
+) -> u32 {
+    // SAFETY: compiled code is only called by `run_native`, with a pointer
+    // to the machine it holds exclusively for the call.
+    let machine = unsafe { &mut *(machine as *mut ChipOxide<'a, I>) };
+    let result = Instruction::try_from(opcode as u16)
+        .and_then(|inst| machine.execute_at(address as u16, inst));
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            if let Some(jit) = &mut machine.jit {
+                jit.fault = Some(error);
+            }
+            1
+        }
+    }
+}
+
+impl<'a, I> ChipOxide<'a, I>
+where
+    I: ChipIO + 'a,
+{
+    // Run this frame's share of `instructions_per_second`, running compiled
+    // blocks where there are some. Blocks never run past the end of the
+    // frame, where the timers tick and the keypad changes, and end at the
+    // instructions that wait on either, so everything happens on the same
+    // instruction as when interpreting.
+    pub(crate) fn run_jit_frame(&mut self) -> Result<(), Error> {
+        if self.jit.is_none() {
+            self.jit = Some(Box::new(Jit::new()?));
+        }
+        let mut due = self.fixed_frame_due() as usize;
+        while due > 0 && !self.exited {
+            let address = self.counter;
+            let last = match self.native_block(address)? {
+                Some(block) if block.len <= due => {
+                    due -= self.run_native(block, due)?;
+                    address + (block.len - 1) * INSTRUCTION_SIZE
+                }
+                _ => {
+                    self.step()?;
+                    due -= 1;
+                    address
+                }
+            };
+            if self.idling(last) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // The compiled block at an address, compiling it once it is hot.
+    fn native_block(&mut self, address: usize) -> Result<Option<Native>, Error> {
+        let jit = self.jit.as_mut().expect("JIT");
+        if let Some(block) = jit.blocks.get(address).copied().flatten() {
+            return Ok(Some(block));
+        }
+        if !jit.heat(address) {
+            return Ok(None);
+        }
+        // Fit blocks in a frame, so they are not passed over at the end.
+        let limit = (self.config.instructions_per_second / self.config.timer_hz.max(1) as u32)
+            .clamp(1, MAX_BLOCK as u32) as usize;
+        let mut insts = vec![];
+        while let Some(inst) = self.instruction_at(address + insts.len() * INSTRUCTION_SIZE) {
+            insts.push(inst);
+            if ends_native_block(&inst, self.config.skip_idle) || insts.len() == limit {
+                break;
+            }
+        }
+        // The idle check looks at the last instruction, so it cannot be an
+        // FX07 that may have been skipped.
+        if insts.len() >= 2
+            && is_skip(&insts[insts.len() - 2])
+            && matches!(insts.last(), Some(Instruction::GetDelay(_)))
+        {
+            insts.pop();
+        }
+        if insts.is_empty() {
+            return Ok(None);
+        }
+        // Looping inside the block skips the idle check between runs, which
+        // only a jump to itself could fail.
+        let loops = insts.last() == Some(&Instruction::Jump(address as u16))
+            && !(insts.len() == 1 && self.config.skip_idle);
+        let layout = Layout {
+            register: offset_of!(Self, register) as i32,
+            index: offset_of!(Self, index) as i32,
+            counter: offset_of!(Self, counter) as i32,
+            delay: offset_of!(Self, delay) as i32,
+            helper: helper::<'a, I>,
+            quirks: self.config.quirks,
+        };
+        let jit = self.jit.as_mut().expect("JIT");
+        jit.compile(address, &insts, loops, &layout).map(Some)
+    }
+
+    // Run a block, returning the instructions it ran.
+    fn run_native(&mut self, block: Native, limit: usize) -> Result<usize, Error> {
+        let limit = limit.min(u32::MAX as usize - 1) as u32;
+        // SAFETY: the block was compiled for this machine's layout, and
+        // nothing else touches the machine while it runs.
+        match unsafe { (block.run)(self as *mut Self as *mut u8, limit) } {
+            FAULT => Err(self
+                .jit
+                .as_mut()
+                .and_then(|jit| jit.fault.take())
+                .unwrap_or_else(|| Error::other("Compiled block failed"))),
+            ran => Ok(ran as usize),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference, ChipConfig, Engine, MockIO};
+    use proptest::prelude::*;
+
+    fn jit_config(quirks: Quirks) -> ChipConfig {
+        ChipConfig {
+            engine: Engine::Jit,
+            instructions_per_second: 60 * 20,
+            skip_idle: false,
+            quirks,
+            // Both engines draw the same CXNN numbers.
+            seed: Some(1),
+            ..ChipConfig::default(false)
+        }
+    }
+
+    #[test]
+    fn needs_fixed_timing() {
+        let config = ChipConfig {
+            timing: crate::Timing::Vip,
+            ..jit_config(Quirks::SCHIP)
+        };
+        let mut io = MockIO::new();
+        assert!(ChipOxide::new(&[0x12, 0x00], &mut io, &config).is_err());
+    }
+
+    #[test]
+    fn encodes_what_it_decodes() {
+        for opcode in 0..=u16::MAX {
+            if let Ok(inst) = Instruction::try_from(opcode) {
+                assert_eq!(Instruction::try_from(super::opcode(inst)).unwrap(), inst);
+            }
+        }
+    }
+
+    #[test]
+    fn overwritten_code_is_recompiled() {
+        // V1 += 1, then overwrite its low byte with 5 and loop back to it.
+        let program = [0x71, 0x01, 0xA2, 0x01, 0x60, 0x05, 0xF0, 0x55, 0x12, 0x00];
+        let config = ChipConfig {
+            instructions_per_second: 60 * 5,
+            ..jit_config(Quirks::SCHIP)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        for _ in 0..20 {
+            chip8.cycle().unwrap();
+        }
+        assert_eq!(chip8.registers()[1], 1 + 5 * 19);
+    }
+
+    #[test]
+    fn loops_run_for_the_rest_of_the_frame() {
+        // V0 += 1, jumping back to it: 50 times a frame.
+        let program = [0x70, 0x01, 0x12, 0x00];
+        let config = ChipConfig {
+            instructions_per_second: 60 * 100,
+            ..jit_config(Quirks::SCHIP)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        for _ in 0..20 {
+            chip8.cycle().unwrap();
+        }
+        assert!(chip8.jit.as_ref().unwrap().blocks[0x200].is_some());
+        assert_eq!(chip8.registers()[0], (20 * 50) as u8);
+        assert_eq!(chip8.counter(), 0x200);
+    }
+
+    #[test]
+    fn delay_waits_are_one_block() {
+        // Delay = 20, then wait for it: FX07; 3X00; 1NNN. Then spin.
+        let program = [
+            0x60, 0x14, 0xF0, 0x15, 0xF1, 0x07, 0x31, 0x00, 0x12, 0x04, 0x12, 0x0A,
+        ];
+        let jit = jit_config(Quirks::SCHIP);
+        let interpreter = ChipConfig {
+            engine: Engine::Interpreter,
+            ..jit_config(Quirks::SCHIP)
+        };
+        let (mut io, mut jit_io) = (MockIO::new(), MockIO::new());
+        let mut interpreted = ChipOxide::new(&program, &mut io, &interpreter).unwrap();
+        let mut compiled = ChipOxide::new(&program, &mut jit_io, &jit).unwrap();
+        for _ in 0..30 {
+            interpreted.cycle().unwrap();
+            compiled.cycle().unwrap();
+            assert_eq!(
+                reference::snapshot(&compiled),
+                reference::snapshot(&interpreted)
+            );
+        }
+        let block = compiled.jit.as_ref().unwrap().blocks[0x204];
+        assert_eq!(block.map(|block| block.len), Some(3));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_the_interpreter(
+            quirks in any::<[bool; 5]>(),
+            opcodes in prop::collection::vec(reference::opcode(), 1..48),
+        ) {
+            let [shift_vy, jump_v0, memory_increment, vf_reset, wrap] = quirks;
+            let quirks = Quirks { shift_vy, jump_v0, memory_increment, vf_reset, wrap, key_release: false };
+            // Loop back to the start, so blocks get hot.
+            let program: Vec<u8> = opcodes.iter().chain([&0x1200]).flat_map(|op| op.to_be_bytes()).collect();
+            let jit = jit_config(quirks);
+            let interpreter = ChipConfig { engine: Engine::Interpreter, ..jit_config(quirks) };
+            let (mut io, mut jit_io) = (MockIO::new(), MockIO::new());
+            let mut interpreted = ChipOxide::new(&program, &mut io, &interpreter).unwrap();
+            let mut compiled = ChipOxide::new(&program, &mut jit_io, &jit).unwrap();
+            for _ in 0..30 {
+                let expected = interpreted.cycle();
+                let result = compiled.cycle();
+                prop_assert_eq!(result.is_ok(), expected.is_ok());
+                prop_assert_eq!(reference::snapshot(&compiled), reference::snapshot(&interpreted));
+                if expected.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{Error, RomInfo, KEYBOARD_SIZE};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::{fs::read_to_string, path::Path};
+
+// The CHIP-8 keypad as laid out on the COSMAC VIP, row by row.
+const HEX_LAYOUT: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+/// Host keyboard layouts with a preset keymap. Each puts the keypad on the
+/// same physical keys, the left four keys of the top four rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+}
+
+impl Layout {
+    // The characters on the keypad's keys, row by row.
+    fn rows(&self) -> [&'static str; 4] {
+        match self {
+            Layout::Qwerty => ["1234", "qwer", "asdf", "zxcv"],
+            Layout::Azerty => ["1234", "azer", "qsdf", "wxcv"],
+            Layout::Qwertz => ["1234", "qwer", "asdf", "yxcv"],
+            Layout::Dvorak => ["1234", "',.p", "aoeu", ";qjk"],
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Error> {
+        match name.to_lowercase().as_str() {
+            "qwerty" => Ok(Layout::Qwerty),
+            "azerty" => Ok(Layout::Azerty),
+            "qwertz" => Ok(Layout::Qwertz),
+            "dvorak" => Ok(Layout::Dvorak),
+            _ => Err(Error::Parse(format!("Unknown layout: {}", name))),
+        }
+    }
+}
+
+/// Maps host keys to CHIP-8 keys.
+///
+/// Host keys are named by the backend: the character they type, such as
+/// `"q"`, or a lowercase name such as `"up"` or `"space"`. Names are
+/// matched case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Keymap {
+    bindings: BTreeMap<String, u8>,
+}
+
+impl Keymap {
+    /// An empty keymap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The preset for a layout.
+    pub fn preset(layout: Layout) -> Self {
+        let mut keymap = Self::new();
+        for (row, keys) in layout.rows().iter().zip(HEX_LAYOUT) {
+            for (host, key) in row.chars().zip(keys) {
+                keymap.bindings.insert(host.to_string(), key);
+            }
+        }
+        // AZERTY types symbols on the number row without shift.
+        if layout == Layout::Azerty {
+            for (host, key) in "&é\"'".chars().zip(HEX_LAYOUT[0]) {
+                keymap.bindings.insert(host.to_string(), key);
+            }
+        }
+        keymap
+    }
+
+    /// Parse a keymap file. Each line is `host = key` with the CHIP-8 key
+    /// in hex, or `layout = name` to start from a preset. `#` starts a
+    /// comment.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut keymap = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid =
+                |what: &str| Error::Parse(format!("Line {}: {}: {}", number + 1, what, line));
+            let (host, value) = line
+                .rsplit_once('=')
+                .ok_or_else(|| invalid("Expected `host = key`"))?;
+            let (host, value) = (host.trim(), value.trim());
+            if host == "layout" {
+                keymap
+                    .bindings
+                    .extend(Self::preset(value.parse()?).bindings);
+                continue;
+            }
+            let key = u8::from_str_radix(value, 16)
+                .ok()
+                .filter(|&key| (key as usize) < KEYBOARD_SIZE)
+                .ok_or_else(|| invalid("Invalid key"))?;
+            keymap.bind(host, key);
+        }
+        Ok(keymap)
+    }
+
+    /// Load a keymap file, see `parse`.
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&read_to_string(path)?)
+    }
+
+    /// Bind a host key, replacing any earlier binding for it.
+    pub fn bind(&mut self, host: &str, key: u8) {
+        self.bindings.insert(host.to_lowercase(), key & 0xF);
+    }
+
+    /// Remove a host key's binding.
+    pub fn unbind(&mut self, host: &str) {
+        self.bindings.remove(&host.to_lowercase());
+    }
+
+    /// The CHIP-8 key a host key is bound to.
+    pub fn get(&self, host: &str) -> Option<u8> {
+        match self.bindings.get(host) {
+            Some(&key) => Some(key),
+            None => self.bindings.get(&host.to_lowercase()).copied(),
+        }
+    }
+
+    /// Add the bindings the ROM database has for a game on top.
+    pub fn with_overrides(mut self, info: &RomInfo) -> Self {
+        for &(host, key) in info.bindings {
+            self.bind(host, key);
+        }
+        self
+    }
+
+    /// Every binding, ordered by host key.
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, u8)> {
+        self.bindings
+            .iter()
+            .map(|(host, &key)| (host.as_str(), key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_share_physical_keys() {
+        let qwerty = Keymap::preset(Layout::Qwerty);
+        assert_eq!(qwerty.get("4"), Some(0xC));
+        assert_eq!(qwerty.get("X"), Some(0x0));
+        assert_eq!(Keymap::preset(Layout::Azerty).get("a"), Some(0x4));
+        assert_eq!(Keymap::preset(Layout::Azerty).get("é"), Some(0x2));
+        assert_eq!(Keymap::preset(Layout::Qwertz).get("y"), Some(0xA));
+        assert_eq!(Keymap::preset(Layout::Dvorak).get("o"), Some(0x8));
+        assert_eq!(qwerty.bindings().count(), 16);
+    }
+
+    #[test]
+    fn parses_files() {
+        let keymap = Keymap::parse("layout = dvorak\n# arrows\nUp = c\ndown=D \n= = 5\n").unwrap();
+        assert_eq!(keymap.get("up"), Some(0xC));
+        assert_eq!(keymap.get("down"), Some(0xD));
+        assert_eq!(keymap.get("="), Some(0x5));
+        assert_eq!(keymap.get("a"), Some(0x7));
+        assert!(Keymap::parse("q = 10").is_err());
+        assert!(Keymap::parse("q").is_err());
+        assert!(Keymap::parse("layout = colemak").is_err());
+    }
+
+    #[test]
+    fn applies_rom_overrides() {
+        let pong = RomInfo::find(include_bytes!("../roms/PONG")).unwrap();
+        let keymap = Keymap::preset(Layout::Qwerty).with_overrides(pong);
+        assert_eq!(keymap.get("up"), Some(0x1));
+        assert_eq!(keymap.get("q"), Some(0x4));
+    }
+}
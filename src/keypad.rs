@@ -0,0 +1,95 @@
+use crate::{Error, KEYBOARD_SIZE};
+
+/// The hex keypad: which keys are down, and which were pressed or released
+/// during the current frame. Bit `n` of each mask is key `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Keypad {
+    down: u16,
+    pressed: u16,
+    released: u16,
+}
+
+impl Keypad {
+    /// A keypad with no keys down.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Press or release one key.
+    pub fn set(&mut self, key: usize, down: bool) -> Result<(), Error> {
+        if key >= KEYBOARD_SIZE {
+            return Err(Error::InvalidKey(key));
+        }
+        let mask = 1 << key;
+        match (self.down & mask != 0, down) {
+            (false, true) => self.pressed |= mask,
+            (true, false) => self.released |= mask,
+            _ => {}
+        }
+        self.down = self.down & !mask | (down as u16) << key;
+        Ok(())
+    }
+
+    /// Replace the whole state at once, for backends that read every key.
+    pub fn set_all(&mut self, down: u16) {
+        self.pressed |= down & !self.down;
+        self.released |= self.down & !down;
+        self.down = down;
+    }
+
+    /// Whether a key is down now.
+    pub fn is_down(&self, key: usize) -> bool {
+        key < KEYBOARD_SIZE && self.down & 1 << key != 0
+    }
+
+    /// Whether a key is down now or was pressed this frame, so taps shorter
+    /// than a frame are not missed.
+    pub fn was_down(&self, key: usize) -> bool {
+        self.is_down(key) || key < KEYBOARD_SIZE && self.pressed & 1 << key != 0
+    }
+
+    /// Keys down now.
+    pub fn down(&self) -> u16 {
+        self.down
+    }
+
+    /// Keys pressed this frame, even if released again since.
+    pub fn pressed(&self) -> u16 {
+        self.pressed
+    }
+
+    /// Keys released this frame, even if pressed again since.
+    pub fn released(&self) -> u16 {
+        self.released
+    }
+
+    /// Start a new frame, forgetting its presses and releases.
+    pub fn begin_frame(&mut self) {
+        self.pressed = 0;
+        self.released = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_presses_and_releases_per_frame() {
+        let mut keypad = Keypad::new();
+        keypad.set(3, true).unwrap();
+        keypad.set(5, true).unwrap();
+        keypad.set(5, false).unwrap();
+        assert_eq!(keypad.down(), 1 << 3);
+        assert_eq!(keypad.pressed(), 1 << 3 | 1 << 5);
+        assert_eq!(keypad.released(), 1 << 5);
+        assert!(keypad.was_down(5) && !keypad.is_down(5));
+
+        keypad.begin_frame();
+        keypad.set_all(1 << 3 | 1 << 0xF);
+        assert_eq!(keypad.pressed(), 1 << 0xF);
+        assert_eq!(keypad.released(), 0);
+        assert!(!keypad.was_down(5));
+        assert!(keypad.set(16, true).is_err());
+    }
+}
@@ -1,5 +1,11 @@
-use log::info;
-use std::{io::Error, thread::sleep, time::Duration};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+use cache::DecodeCache;
+use core::ops::Range;
+use rng::Rng;
 
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
@@ -10,6 +16,7 @@ const REGISTER_SIZE: usize = 16;
 const COUNTER_START: usize = 0x200;
 const INSTRUCTION_SIZE: usize = 2;
 const FONT_SIZE: u16 = 5;
+const STACK_SIZE: usize = 16;
 const VF: usize = 0xF;
 
 const FONT_DATA: [[u8; 5]; 16] = [
@@ -31,26 +38,94 @@ const FONT_DATA: [[u8; 5]; 16] = [
     [0xF0, 0x80, 0xF0, 0x80, 0x80], // F
 ];
 
+mod analysis;
+mod blend;
+mod cache;
+mod capture;
+mod clock;
 mod config;
+mod damage;
+mod database;
+mod error;
+mod framebuffer;
+mod idle;
 mod instruction;
 mod io;
+#[cfg(feature = "jit")]
+mod jit;
+mod keymap;
+mod keypad;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
 mod opcodes;
+mod recompile;
+#[cfg(test)]
+mod reference;
+mod render;
+mod rng;
+mod scheduler;
+mod timing;
 
-pub use config::ChipConfig;
-pub use io::ChipIO;
+pub use analysis::{Analysis, Confidence, Finding, Pattern, Quirk, Suggestion};
+pub use blend::{Blend, FrameBlender};
+#[cfg(feature = "std")]
+pub use capture::write_pbm;
+#[cfg(feature = "capture")]
+pub use capture::write_png;
+pub use capture::Recorder;
+#[cfg(feature = "std")]
+pub use clock::RealClock;
+pub use clock::{Clock, ManualClock};
+pub use config::{ChipConfig, Engine, Platform, Quirks};
+pub use damage::{Damage, Rect};
+pub use database::RomInfo;
+pub use error::Error;
+pub use framebuffer::{Framebuffer, Resolution};
+pub use instruction::Instruction;
+pub use io::{ChipIO, Command};
+pub use keymap::{Keymap, Layout};
+pub use keypad::Keypad;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::{Beep, Frame, KeyEvent, MockIO};
+pub use recompile::{recompile, Block, Parts, Recompiled};
+pub use render::{Image, Palette, Renderer};
+pub use scheduler::Scheduler;
+pub use timing::Timing;
 
-use instruction::Instruction;
+// Progress of an FX0A instruction waiting for a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyWait {
+    Idle,
+    // Waiting for a press, ignoring the keys that were already down.
+    Press(u16),
+    // Waiting for this key to be released.
+    Release(u8),
+}
 
 /// The ChipOxide Struct
 pub struct ChipOxide<'a, I: ChipIO> {
     memory: [u8; MEM_SIZE],
-    screen: [[bool; SCREEN_HEIGHT]; SCREEN_WIDTH],
+    decoded: DecodeCache,
+    screen: Framebuffer,
+    damage: Damage,
     stack: Vec<u16>,
     register: [u8; REGISTER_SIZE],
-    timer: (u8, u8), // Delay Timer, Sound Timer
-    keyboard: [bool; KEYBOARD_SIZE],
+    delay: u8,
+    sound: u8,
+    keypad: Keypad,
+    key_wait: KeyWait,
+    frames: u64,
+    // VIP machine cycles left in this frame, negative if overrun.
+    cycle_budget: i64,
+    idle_frames: u64,
+    paused: bool,
+    exited: bool,
     counter: usize,
     index: u16,
+    program: Vec<u8>,
+    rng: Rng,
+    #[cfg(feature = "jit")]
+    jit: Option<Box<jit::Jit>>,
     io: &'a mut I,
     config: &'a ChipConfig,
 }
@@ -63,73 +138,488 @@ where
     fn empty(io: &'a mut I, config: &'a ChipConfig) -> Self {
         Self {
             memory: [0; MEM_SIZE],
-            screen: [[false; SCREEN_HEIGHT]; SCREEN_WIDTH],
+            decoded: DecodeCache::default(),
+            screen: Framebuffer::default(),
+            damage: Damage::default(),
             stack: vec![],
             register: [0; REGISTER_SIZE],
-            timer: (0, 0),
-            keyboard: [false; KEYBOARD_SIZE],
+            delay: 0,
+            sound: 0,
+            keypad: Keypad::new(),
+            key_wait: KeyWait::Idle,
+            frames: 0,
+            cycle_budget: 0,
+            idle_frames: 0,
+            paused: false,
+            exited: false,
             counter: 0,
             index: 0,
+            program: vec![],
+            rng: Rng::new(config.seed),
+            #[cfg(feature = "jit")]
+            jit: None,
             io,
             config,
         }
     }
 
-    // Load and put a program in loop.
-    pub fn start(program: &[u8], io: &'a mut I, config: &'a ChipConfig) -> Result<(), Error> {
+    /// Create a machine with the font and `program` loaded into memory.
+    pub fn new(program: &[u8], io: &'a mut I, config: &'a ChipConfig) -> Result<Self, Error> {
+        if program.len() > MEM_SIZE - COUNTER_START {
+            return Err(Error::ProgramTooLarge(program.len()));
+        }
+        #[cfg(feature = "jit")]
+        if config.engine == Engine::Jit && config.timing != Timing::Fixed {
+            return Err(Error::other("The JIT engine needs Timing::Fixed"));
+        }
         let mut chip8 = Self::empty(io, config);
+        chip8.program = program.to_vec();
+        chip8.load_program();
+        Ok(chip8)
+    }
 
-        for font in FONT_DATA {
-            for byte in font {
-                chip8.memory[chip8.counter] = byte;
-                chip8.counter += 1;
-            }
+    /// Put the machine back as it was when created, reloading the program.
+    /// Keys held on the host count as released until pressed again.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        if self.sound != 0 {
+            self.io.end_beep()?;
         }
+        // Naming every field means a new one has to be reset here too.
+        let Self {
+            memory,
+            decoded: _,
+            screen,
+            damage,
+            stack,
+            register,
+            delay,
+            sound,
+            keypad,
+            key_wait,
+            frames,
+            cycle_budget,
+            idle_frames,
+            paused,
+            exited,
+            counter: _,
+            index,
+            program: _,
+            rng,
+            #[cfg(feature = "jit")]
+                jit: _,
+            io: _,
+            config,
+        } = self;
+        *memory = [0; MEM_SIZE];
+        *screen = Framebuffer::default();
+        damage.add(Rect::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT));
+        stack.clear();
+        *register = [0; REGISTER_SIZE];
+        *delay = 0;
+        *sound = 0;
+        *keypad = Keypad::new();
+        *key_wait = KeyWait::Idle;
+        *frames = 0;
+        *cycle_budget = 0;
+        *idle_frames = 0;
+        *paused = false;
+        *exited = false;
+        *index = 0;
+        *rng = Rng::new(config.seed);
+        // Clears what was decoded and compiled, and resets the counter.
+        self.load_program();
+        Ok(())
+    }
 
-        chip8.counter = COUNTER_START;
-        for byte in program {
-            chip8.memory[chip8.counter] = *byte;
-            chip8.counter += 1;
+    // Put the font and program into memory.
+    fn load_program(&mut self) {
+        self.decoded.clear();
+        #[cfg(feature = "jit")]
+        {
+            self.jit = None;
         }
-        chip8.counter = COUNTER_START;
+        for (i, byte) in FONT_DATA.iter().flatten().enumerate() {
+            self.memory[i] = *byte;
+        }
+        self.memory[COUNTER_START..COUNTER_START + self.program.len()]
+            .copy_from_slice(&self.program);
+        self.counter = COUNTER_START;
+    }
 
-        info!("Starting Chip Oxide");
+    // Forget whatever was decoded or compiled from memory just written.
+    fn written(&mut self, range: Range<usize>) {
+        #[cfg(feature = "jit")]
+        if let Some(jit) = &mut self.jit {
+            jit.invalidate(range.clone());
+        }
+        self.decoded.invalidate(range);
+    }
+
+    /// Load a program and run it in real time until it exits, returning
+    /// the machine as it was left.
+    #[cfg(feature = "std")]
+    pub fn start(program: &[u8], io: &'a mut I, config: &'a ChipConfig) -> Result<Self, Error> {
+        let mut chip8 = Self::new(program, io, config)?;
+
+        log::info!("Starting Chip Oxide");
 
-        // Mailoop.
-        loop {
-            sleep(Duration::from_millis(
-                ((1.0 / chip8.config.timer_hz as f64) * 1000.0) as u64,
-            ));
-            chip8.update_timer()?;
-            for _ in 0..chip8.config.opcodes_per_cycle {
-                if let Some((key, state)) = chip8.io.get_key()? {
-                    chip8.keyboard[key] = state;
+        let hz = chip8.config.timer_hz as u32;
+        chip8.run(&mut Scheduler::new(RealClock::new(), hz))?;
+        log::info!("Chip Oxide exited");
+        Ok(chip8)
+    }
+
+    /// Run cycles whenever the scheduler says they are due, following the
+    /// backend's commands in between, until the machine exits.
+    pub fn run<C: Clock>(&mut self, scheduler: &mut Scheduler<C>) -> Result<(), Error> {
+        while !self.exited {
+            while let Some(command) = self.io.next_command()? {
+                self.control(command, scheduler)?;
+            }
+            // Frames due while paused are dropped, so resuming does not
+            // catch up on them.
+            for _ in 0..scheduler.advance() {
+                if self.paused || self.exited {
+                    break;
                 }
-                let inst = chip8.fetch_instruction()?;
-                chip8.execute_instruction(inst)?;
+                self.cycle()?;
+            }
+            if !self.exited {
+                scheduler.wait();
+            }
+        }
+        Ok(())
+    }
+
+    /// Carry out a command from the backend.
+    pub fn control<C: Clock>(
+        &mut self,
+        command: Command,
+        scheduler: &mut Scheduler<C>,
+    ) -> Result<(), Error> {
+        match command {
+            Command::Speed(percent) => scheduler.set_speed(percent),
+            Command::Pause => self.paused = true,
+            Command::Resume => self.paused = false,
+            Command::TogglePause => self.paused = !self.paused,
+            Command::Step => {
+                self.paused = true;
+                self.cycle()?;
             }
+            Command::Reset => self.reset()?,
+            Command::Exit => self.exit(),
         }
+        Ok(())
+    }
+
+    // Stop running, at the end of the current instruction.
+    fn exit(&mut self) {
+        self.exited = true;
+    }
+
+    /// Number of frames cut short because the program was busy-waiting,
+    /// see `ChipConfig::skip_idle`.
+    pub fn idle_frames(&self) -> u64 {
+        self.idle_frames
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether the program or the backend asked to stop.
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Write `bytes` into memory at `address`, such as a setting a program
+    /// expects its launcher to have left there.
+    pub fn write_memory(&mut self, address: u16, bytes: &[u8]) -> Result<(), Error> {
+        let range = address as usize..address as usize + bytes.len();
+        let Some(memory) = self.memory.get_mut(range.clone()) else {
+            return Err(Error::MemoryOutOfBounds {
+                address,
+                len: bytes.len(),
+            });
+        };
+        memory.copy_from_slice(bytes);
+        self.written(range);
+        Ok(())
+    }
+
+    pub fn screen(&self) -> &Framebuffer {
+        &self.screen
+    }
+
+    pub fn registers(&self) -> &[u8; REGISTER_SIZE] {
+        &self.register
+    }
+
+    /// The delay and sound timers.
+    pub fn timers(&self) -> (u8, u8) {
+        (self.delay, self.sound)
+    }
+
+    pub fn stack(&self) -> &[u16] {
+        &self.stack
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Address of the next instruction.
+    pub fn counter(&self) -> usize {
+        self.counter
+    }
+
+    pub fn keypad(&self) -> &Keypad {
+        &self.keypad
+    }
+
+    /// Run one timer cycle: tick the timers, update the keypad, execute this
+    /// frame's instructions as `timing` says, then present the frame.
+    pub fn cycle(&mut self) -> Result<(), Error> {
+        self.begin_frame()?;
+        match self.config.timing {
+            Timing::Fixed => self.run_fixed_frame()?,
+            Timing::Vip => self.run_vip_frame()?,
+        }
+        self.present()
+    }
+
+    // Tick the timers and bring the keypad up to date.
+    fn begin_frame(&mut self) -> Result<(), Error> {
+        self.update_timer()?;
+        self.keypad.begin_frame();
+        self.io.update_keypad(&mut self.keypad)
+    }
+
+    // This frame's share of `instructions_per_second`, spreading them over
+    // frames exactly however they divide.
+    fn fixed_frame_due(&mut self) -> u64 {
+        let (ips, hz) = (
+            self.config.instructions_per_second as u64,
+            self.config.timer_hz.max(1) as u64,
+        );
+        let due = (self.frames + 1) * ips / hz - self.frames * ips / hz;
+        self.frames += 1;
+        due
+    }
+
+    fn run_fixed_frame(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "jit")]
+        if self.config.engine == Engine::Jit {
+            return self.run_jit_frame();
+        }
+        for _ in 0..self.fixed_frame_due() {
+            if self.exited {
+                break;
+            }
+            let address = self.counter;
+            self.step()?;
+            if self.idling(address) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch and execute a single instruction.
+    pub fn step(&mut self) -> Result<(), Error> {
+        let inst = self.fetch_instruction()?;
+        self.execute_instruction(inst)
     }
 
     // Update the delay timer and the sound timer.
     fn update_timer(&mut self) -> Result<(), Error> {
-        if self.timer.0 != 0 {
-            self.timer.0 -= 1;
+        if self.delay != 0 {
+            self.delay -= 1;
         }
-        if self.timer.1 != 0 {
-            self.timer.1 -= 1;
-            if self.timer.1 == 0 {
+        if self.sound != 0 {
+            self.sound -= 1;
+            if self.sound == 0 {
                 self.io.end_beep()?
             }
         }
         Ok(())
     }
 
+    // Hand the screen and what changed on it to the IO.
+    fn present(&mut self) -> Result<(), Error> {
+        self.io.present_frame(&self.screen, self.damage.rects())?;
+        self.damage.clear();
+        Ok(())
+    }
+
     // Fetch the instruction from memory.
     fn fetch_instruction(&mut self) -> Result<Instruction, Error> {
+        let address = self.counter;
+        if let Some(inst) = self.decoded.get(address) {
+            self.counter += INSTRUCTION_SIZE;
+            return Ok(inst);
+        }
+        let opcode = match self.memory.get(address..address + INSTRUCTION_SIZE) {
+            Some(&[high, low]) => (high as u16) << 8 | low as u16,
+            _ => return Err(Error::CounterOutOfBounds(address)),
+        };
         self.counter += INSTRUCTION_SIZE;
-        Instruction::try_from(
-            (self.memory[self.counter - 1] as u16) | ((self.memory[self.counter - 2] as u16) << 8),
-        )
+        let inst = Instruction::try_from(opcode)?;
+        self.decoded.insert(address, inst);
+        Ok(inst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn spreads_instructions_over_frames() {
+        // V0 += 1, over and over.
+        let program = [0x70, 0x01].repeat(40);
+        let config = ChipConfig {
+            instructions_per_second: 100,
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.register[0], 1);
+        for _ in 1..12 {
+            chip8.cycle().unwrap();
+        }
+        assert_eq!(chip8.register[0], 20);
+    }
+
+    #[test]
+    fn follows_commands() {
+        // V0 += 1, over and over.
+        let program = [0x70, 0x01].repeat(40);
+        let config = ChipConfig {
+            instructions_per_second: 60,
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+
+        chip8.control(Command::Step, &mut scheduler).unwrap();
+        chip8.control(Command::Step, &mut scheduler).unwrap();
+        assert!(chip8.is_paused());
+        assert_eq!(chip8.register[0], 2);
+        chip8.control(Command::TogglePause, &mut scheduler).unwrap();
+        assert!(!chip8.is_paused());
+
+        chip8.control(Command::Speed(300), &mut scheduler).unwrap();
+        assert_eq!(scheduler.speed(), 300);
+
+        chip8.memory[COUNTER_START] = 0x12;
+        chip8.control(Command::Reset, &mut scheduler).unwrap();
+        assert_eq!(chip8.register[0], 0);
+        assert_eq!(chip8.counter, COUNTER_START);
+        assert_eq!(chip8.memory[COUNTER_START], 0x70);
+        assert!(!chip8.damage.is_empty());
+    }
+
+    #[test]
+    fn writes_memory() {
+        // V0 += 1, then loop back to it.
+        let program = [0x70, 0x01, 0x12, 0x00];
+        let config = ChipConfig::default(false);
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.step().unwrap();
+        // Make it V0 += 2, past what was decoded.
+        chip8
+            .write_memory(COUNTER_START as u16 + 1, &[0x02])
+            .unwrap();
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.register[0], 3);
+        assert!(chip8.write_memory(MEM_SIZE as u16 - 1, &[0, 0]).is_err());
+    }
+
+    #[test]
+    fn reset_matches_a_new_machine() {
+        // Hires, V0 = random, draw, FX55, wait for a key, pause.
+        let program = [
+            0x00, 0xFF, 0xC0, 0xFF, 0xD0, 0x05, 0xF3, 0x55, 0xF1, 0x0A, 0x12, 0x0A,
+        ];
+        let config = ChipConfig {
+            seed: Some(7),
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        io.script(KeyEvent {
+            cycle: 1,
+            key: 5,
+            pressed: true,
+        });
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        for _ in 0..3 {
+            chip8.cycle().unwrap();
+        }
+        chip8.control(Command::Pause, &mut scheduler).unwrap();
+        chip8.reset().unwrap();
+
+        let mut fresh_io = MockIO::new();
+        let mut fresh = ChipOxide::new(&program, &mut fresh_io, &config).unwrap();
+        assert_eq!(chip8.memory, fresh.memory);
+        assert_eq!(chip8.screen, fresh.screen);
+        assert_eq!(chip8.stack, fresh.stack);
+        assert_eq!(chip8.register, fresh.register);
+        assert_eq!(chip8.timers(), fresh.timers());
+        assert_eq!(chip8.keypad, fresh.keypad);
+        assert_eq!(chip8.key_wait, fresh.key_wait);
+        assert_eq!(chip8.frames, fresh.frames);
+        assert_eq!(chip8.cycle_budget, fresh.cycle_budget);
+        assert_eq!(chip8.idle_frames, fresh.idle_frames);
+        assert_eq!(chip8.paused, fresh.paused);
+        assert_eq!(chip8.exited, fresh.exited);
+        assert_eq!(chip8.counter, fresh.counter);
+        assert_eq!(chip8.index, fresh.index);
+        assert_eq!(chip8.rng.next_u8(), fresh.rng.next_u8());
+    }
+
+    #[test]
+    fn exits_on_00fd() {
+        // V0 = 1, exit, V0 = 2.
+        let program = [0x60, 0x01, 0x00, 0xFD, 0x60, 0x02];
+        let config = ChipConfig::default(false);
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        chip8.run(&mut scheduler).unwrap();
+        assert!(chip8.has_exited());
+        assert_eq!(chip8.registers()[0], 1);
+        assert_eq!(chip8.counter(), COUNTER_START + 4);
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_programs_never_panic(
+            legacy in any::<bool>(),
+            program in prop::collection::vec(any::<u8>(), 0..512),
+            keys in prop::collection::vec((0u64..512, 0usize..20, any::<bool>()), 0..16),
+        ) {
+            let config = ChipConfig::default(legacy);
+            let mut io = MockIO::new();
+            for (cycle, key, pressed) in keys {
+                io.script(KeyEvent { cycle, key, pressed });
+            }
+            let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+            for _ in 0..64 {
+                if chip8.cycle().is_err() {
+                    break;
+                }
+            }
+        }
     }
 }
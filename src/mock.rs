@@ -0,0 +1,247 @@
+use crate::{ChipIO, Command, Error, Framebuffer, Keypad, Rect};
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// A screen handed to `present_frame`, stamped with the cycle it was presented on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub cycle: u64,
+    pub screen: Framebuffer,
+    pub damage: Vec<Rect>,
+}
+
+/// A change of the beeper, stamped with the cycle it happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Beep {
+    Start(u64),
+    End(u64),
+}
+
+/// A scripted key change, delivered on the first keypad update at or after
+/// `cycle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub cycle: u64,
+    pub key: usize,
+    pub pressed: bool,
+}
+
+/// IO that records everything the core does with it, for tests.
+///
+/// Cycles are counted in keypad updates. The core updates the keypad once
+/// at the start of every `cycle`, so a timestamp of `n` means "during the
+/// `n`th cycle".
+#[derive(Debug, Default)]
+pub struct MockIO {
+    cycle: u64,
+    frames: Vec<Frame>,
+    beeps: Vec<Beep>,
+    keys: VecDeque<KeyEvent>,
+    commands: VecDeque<(u64, Command)>,
+}
+
+impl MockIO {
+    /// A mock with no scripted input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Script a key press for the given cycle.
+    pub fn press(&mut self, cycle: u64, key: usize) -> &mut Self {
+        self.script(KeyEvent {
+            cycle,
+            key,
+            pressed: true,
+        })
+    }
+
+    /// Script a key release for the given cycle.
+    pub fn release(&mut self, cycle: u64, key: usize) -> &mut Self {
+        self.script(KeyEvent {
+            cycle,
+            key,
+            pressed: false,
+        })
+    }
+
+    /// Queue a key event. Events are kept in cycle order.
+    pub fn script(&mut self, event: KeyEvent) -> &mut Self {
+        let at = self.keys.partition_point(|e| e.cycle <= event.cycle);
+        self.keys.insert(at, event);
+        self
+    }
+
+    /// Queue a command, handed out once `cycle` cycles have run.
+    pub fn command(&mut self, cycle: u64, command: Command) -> &mut Self {
+        let at = self.commands.partition_point(|&(c, _)| c <= cycle);
+        self.commands.insert(at, (cycle, command));
+        self
+    }
+
+    /// Number of cycles seen so far.
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// Every presented frame, oldest first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Every beeper change, oldest first.
+    pub fn beeps(&self) -> &[Beep] {
+        &self.beeps
+    }
+
+    /// Scripted key events not yet consumed by the core.
+    pub fn pending_keys(&self) -> impl Iterator<Item = &KeyEvent> {
+        self.keys.iter()
+    }
+}
+
+impl ChipIO for MockIO {
+    fn present_frame(&mut self, screen: &Framebuffer, damage: &[Rect]) -> Result<(), Error> {
+        self.frames.push(Frame {
+            cycle: self.cycle,
+            screen: screen.clone(),
+            damage: damage.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn start_beep(&mut self) -> Result<(), Error> {
+        self.beeps.push(Beep::Start(self.cycle));
+        Ok(())
+    }
+
+    fn end_beep(&mut self) -> Result<(), Error> {
+        self.beeps.push(Beep::End(self.cycle));
+        Ok(())
+    }
+
+    fn next_command(&mut self) -> Result<Option<Command>, Error> {
+        match self.commands.front() {
+            Some(&(cycle, _)) if cycle <= self.cycle => {
+                Ok(self.commands.pop_front().map(|(_, command)| command))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn update_keypad(&mut self, keypad: &mut Keypad) -> Result<(), Error> {
+        self.cycle += 1;
+        while let Some(event) = self.keys.front().filter(|e| e.cycle <= self.cycle) {
+            keypad.set(event.key, event.pressed)?;
+            self.keys.pop_front();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChipConfig, ChipOxide, ManualClock, Scheduler};
+
+    // Run one instruction per cycle, so cycles count instructions.
+    fn run(program: &[u8], io: &mut MockIO, steps: usize) -> [u8; 16] {
+        let config = ChipConfig {
+            instructions_per_second: 60,
+            present_on_draw: true,
+            ..ChipConfig::default(false)
+        };
+        let mut chip8 = ChipOxide::new(program, io, &config).unwrap();
+        for _ in 0..steps {
+            chip8.cycle().unwrap();
+        }
+        chip8.register
+    }
+
+    #[test]
+    fn records_a_frame_per_draw() {
+        // V0 = 0, I = font "0", draw at (0, 0) twice.
+        let program = [0x60, 0x00, 0xA0, 0x00, 0xD0, 0x05, 0xD0, 0x05];
+        let mut io = MockIO::new();
+        run(&program, &mut io, 4);
+
+        let frames: Vec<_> = io
+            .frames()
+            .iter()
+            .filter(|f| !f.damage.is_empty())
+            .collect();
+        assert_eq!(io.frames().len(), 6);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].cycle, 3);
+        assert_eq!(frames[0].damage, [Rect::new(0, 0, 8, 5)]);
+        assert!(frames[0].screen.get(0, 0));
+        assert!(!frames[0].screen.get(1, 1));
+        assert_eq!(frames[1].cycle, 4);
+        assert!(!frames[1].screen.get(0, 0));
+    }
+
+    #[test]
+    fn records_a_frame_per_cycle() {
+        // Clear, V0 = 0, I = font "0", draw at (0, 0), then spin.
+        let program = [0x00, 0xE0, 0x60, 0x00, 0xA0, 0x00, 0xD0, 0x05, 0x12, 0x08];
+        let mut io = MockIO::new();
+        let config = ChipConfig::default(false);
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle().unwrap();
+        chip8.cycle().unwrap();
+
+        let frames = io.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].cycle, 1);
+        assert_eq!(frames[0].damage, [Rect::new(0, 0, 64, 32)]);
+        assert!(frames[0].screen.get(0, 0));
+        assert_eq!(frames[1].cycle, 2);
+        assert!(frames[1].damage.is_empty());
+    }
+
+    #[test]
+    fn records_beep_start_and_end() {
+        // V0 = 3, sound = V0, V1 = 0, sound = V1.
+        let program = [0x60, 0x03, 0xF0, 0x18, 0x61, 0x00, 0xF1, 0x18];
+        let mut io = MockIO::new();
+        run(&program, &mut io, 4);
+        assert_eq!(io.beeps(), &[Beep::Start(2), Beep::End(4)]);
+    }
+
+    #[test]
+    fn delivers_scripted_keys_on_time() {
+        // V0 = 5, skip if key V0 down, V1 = 1, V2 = 1.
+        let program = [0x60, 0x05, 0xE0, 0x9E, 0x61, 0x01, 0x62, 0x01];
+        let mut io = MockIO::new();
+        io.press(2, 5).release(10, 5);
+        let register = run(&program, &mut io, 3);
+        assert_eq!((register[1], register[2]), (0, 1));
+        assert_eq!(io.cycle(), 3);
+        assert_eq!(io.pending_keys().count(), 1);
+    }
+
+    #[test]
+    fn delivers_simultaneous_keys_together() {
+        // V0 = 5, V1 = 6, skip V2 = 1 if key V0 down, skip V3 = 1 if key V1 down.
+        let program = [
+            0x60, 0x05, 0x61, 0x06, 0xE0, 0x9E, 0x62, 0x01, 0xE1, 0x9E, 0x63, 0x01,
+        ];
+        let mut io = MockIO::new();
+        io.press(1, 5).press(1, 6);
+        let register = run(&program, &mut io, 4);
+        assert_eq!((register[2], register[3]), (0, 0));
+    }
+
+    #[test]
+    fn hands_out_commands_on_time() {
+        // Spin.
+        let program = [0x12, 0x00];
+        let config = ChipConfig::default(false);
+        let mut io = MockIO::new();
+        io.command(10, Command::Exit);
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8
+            .run(&mut Scheduler::new(ManualClock::new(), 60))
+            .unwrap();
+        assert!(chip8.has_exited());
+        assert_eq!(io.cycle(), 10);
+    }
+}
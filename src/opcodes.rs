@@ -1,19 +1,28 @@
 use crate::{
-    ChipIO, ChipOxide, Instruction, FONT_SIZE, INSTRUCTION_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH, VF,
+    ChipIO, ChipOxide, Error, Instruction, KeyWait, Rect, Resolution, FONT_SIZE, INSTRUCTION_SIZE,
+    MEM_SIZE, STACK_SIZE, VF,
 };
-use log::info;
-use std::io::Error;
+use core::ops::Range;
+use log::trace;
 
 impl<'a, I> ChipOxide<'a, I>
 where
     I: ChipIO,
 {
-    // Execute the instructions.
+    // Execute the instructions. Worth inlining: compiled blocks pass
+    // constants, so each keeps only the code for the instruction it runs.
+    #[inline]
     pub fn execute_instruction(&mut self, inst: Instruction) -> Result<(), Error> {
-        info!("Instruction: {:?}", inst);
+        trace!("Instruction: {:?}", inst);
         match inst {
             Instruction::Clear => self.clear_screen(),
             Instruction::Return => self.return_subroutine(),
+            Instruction::Exit => {
+                self.exit();
+                Ok(())
+            }
+            Instruction::LowRes => self.set_resolution(Resolution::Low),
+            Instruction::HighRes => self.set_resolution(Resolution::High),
             Instruction::Jump(addr) => self.jump(addr),
             Instruction::SubRoutine(addr) => self.subroutine(addr),
             Instruction::SkipED(r, data) => self.skip_ed(r, data),
@@ -51,12 +60,19 @@ where
     }
     // Instructions as functions.
     fn clear_screen(&mut self) -> Result<(), Error> {
-        self.screen = [[false; SCREEN_HEIGHT]; SCREEN_WIDTH];
+        self.screen.clear();
+        self.damage_all();
+        Ok(())
+    }
+
+    fn set_resolution(&mut self, resolution: Resolution) -> Result<(), Error> {
+        self.screen.set_resolution(resolution);
+        self.damage_all();
         Ok(())
     }
 
     fn return_subroutine(&mut self) -> Result<(), Error> {
-        self.counter = self.stack.pop().unwrap() as usize;
+        self.counter = self.stack.pop().ok_or(Error::StackUnderflow)? as usize;
         Ok(())
     }
 
@@ -66,6 +82,9 @@ where
     }
 
     fn subroutine(&mut self, location: u16) -> Result<(), Error> {
+        if self.stack.len() == STACK_SIZE {
+            return Err(Error::StackOverflow);
+        }
         self.stack.push(self.counter as u16);
         self.counter = location as usize;
         Ok(())
@@ -104,24 +123,27 @@ where
 
     fn binary_or(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
         self.register[register0 as usize] |= self.register[register1 as usize];
+        self.reset_vf();
         Ok(())
     }
 
     fn binary_and(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
         self.register[register0 as usize] &= self.register[register1 as usize];
+        self.reset_vf();
         Ok(())
     }
 
     fn logical_xor(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
         self.register[register0 as usize] ^= self.register[register1 as usize];
+        self.reset_vf();
         Ok(())
     }
 
     fn add_register_register(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
         let sum =
             self.register[register0 as usize] as u16 + self.register[register1 as usize] as u16;
-        self.register[VF] = (sum > 255) as u8;
         self.register[register0 as usize] = (sum & 0xFF) as u8;
+        self.register[VF] = (sum > 255) as u8;
         Ok(())
     }
 
@@ -130,23 +152,19 @@ where
             self.register[register0 as usize],
             self.register[register1 as usize],
         );
-        self.register[VF] = (vx > vy) as u8;
         self.register[register0 as usize] = vx.wrapping_sub(vy);
+        self.register[VF] = (vx >= vy) as u8;
         Ok(())
     }
 
     fn shift_right(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
-        let vy = if self.config.legacy {
+        let vy = if self.config.quirks.shift_vy {
             self.register[register1 as usize]
         } else {
             self.register[register0 as usize]
         };
-        self.register[VF] = ((vy & 0x1) == 1) as u8;
-        if self.config.legacy {
-            self.register[register0 as usize] = vy >> 1;
-        } else {
-            self.register[register0 as usize] /= 2;
-        }
+        self.register[register0 as usize] = vy >> 1;
+        self.register[VF] = vy & 0x1;
         Ok(())
     }
 
@@ -155,23 +173,19 @@ where
             self.register[register0 as usize],
             self.register[register1 as usize],
         );
-        self.register[VF] = (vy > vx) as u8;
         self.register[register0 as usize] = vy.wrapping_sub(vx);
+        self.register[VF] = (vy >= vx) as u8;
         Ok(())
     }
 
     fn shift_left(&mut self, register0: u8, register1: u8) -> Result<(), Error> {
-        let vy = if self.config.legacy {
+        let vy = if self.config.quirks.shift_vy {
             self.register[register1 as usize]
         } else {
             self.register[register0 as usize]
-        } << 1;
-        self.register[VF] = ((vy & 0x1) == 1) as u8;
-        if self.config.legacy {
-            self.register[register0 as usize] = vy >> 1;
-        } else {
-            self.register[register0 as usize] /= 2;
-        }
+        };
+        self.register[register0 as usize] = vy << 1;
+        self.register[VF] = vy >> 7;
         Ok(())
     }
 
@@ -188,85 +202,117 @@ where
 
     fn offset_jump(&mut self, register: u8, location: u16) -> Result<(), Error> {
         self.counter = (location
-            + self.register[register as usize * self.config.legacy as usize] as u16)
+            + self.register[register as usize * !self.config.quirks.jump_v0 as usize] as u16)
             as usize;
         Ok(())
     }
 
     fn random(&mut self, register: u8, modifier: u8) -> Result<(), Error> {
-        self.register[register as usize] = rand::random::<u8>() & modifier;
+        self.register[register as usize] = self.rng.next_u8() & modifier;
         Ok(())
     }
 
     fn draw(&mut self, xa: u8, ya: u8, n: u8) -> Result<(), Error> {
-        let x: usize = (self.register[xa as usize] & ((SCREEN_WIDTH as u8) - 1)).into();
-        let y: usize = (self.register[ya as usize] & ((SCREEN_HEIGHT as u8) - 1)).into();
-        self.register[0xF] = 0;
-        for r in 0..n {
-            let r = r as usize;
-            let b = self.memory[(self.index as usize) + r];
-            if y + r == SCREEN_HEIGHT - 1 {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        let x = self.register[xa as usize] as usize % width;
+        let y = self.register[ya as usize] as usize % height;
+        let wrap = self.config.quirks.wrap;
+        let sprite = &self.memory[memory_range(self.index, n as usize)?];
+        self.register[VF] = 0;
+        for (r, b) in sprite.iter().enumerate() {
+            if y + r >= height && !wrap {
                 break;
             }
             for p in 0..8 {
-                let p = p as usize;
-                if x + p == SCREEN_WIDTH - 1 {
+                if x + p >= width && !wrap {
                     break;
                 }
                 let sprite_pixel = ((b << p) & 0b10000000) != 0;
-                if sprite_pixel {
-                    if self.screen[x + p][y + r] {
-                        self.screen[x + p][y + r] = false;
-                        self.register[VF] = 1;
-                    } else {
-                        self.screen[x + p][y + r] = true;
-                    }
+                if sprite_pixel && self.screen.toggle(0, (x + p) % width, (y + r) % height) {
+                    self.register[VF] = 1;
                 }
             }
         }
-        self.io.update_screen(&self.screen).unwrap();
+        // A wrapped sprite can cover all four corners.
+        for (x, w) in spans(x, 8, width, wrap) {
+            for (y, h) in spans(y, sprite.len(), height, wrap) {
+                self.damage.add(Rect::new(x, y, w, h));
+            }
+        }
+        if self.config.present_on_draw {
+            self.present()?;
+        }
         Ok(())
     }
 
     fn key_pressed(&mut self, register: u8) -> Result<(), Error> {
-        if self.keyboard[self.register[register as usize] as usize] {
-            self.keyboard[self.register[register as usize] as usize] = false;
+        let key = (self.register[register as usize] & 0xF) as usize;
+        if self.keypad.was_down(key) {
             self.counter += INSTRUCTION_SIZE;
         }
         Ok(())
     }
 
     fn key_released(&mut self, register: u8) -> Result<(), Error> {
-        if !self.keyboard[self.register[register as usize] as usize] {
+        let key = (self.register[register as usize] & 0xF) as usize;
+        if !self.keypad.was_down(key) {
             self.counter += INSTRUCTION_SIZE;
         }
-        self.keyboard[self.register[register as usize] as usize] = false;
         Ok(())
     }
 
     fn get_delay(&mut self, register: u8) -> Result<(), Error> {
-        self.register[register as usize] = self.timer.0;
+        self.register[register as usize] = self.delay;
         Ok(())
     }
 
+    // FX0A only counts keys pressed after it starts waiting, so a key held
+    // from before does not finish it straight away.
     fn key_wait(&mut self, register: u8) -> Result<(), Error> {
-        if let Some(key) = self.keyboard.iter().position(|x| *x == true) {
-            self.keyboard[key] = false;
-            self.register[register as usize] = key as u8;
-        } else {
-            self.counter -= INSTRUCTION_SIZE;
+        let keypad = self.keypad;
+        self.key_wait = match self.key_wait {
+            KeyWait::Idle => KeyWait::Press(keypad.down()),
+            KeyWait::Press(held) => KeyWait::Press(held & keypad.down()),
+            wait => wait,
+        };
+        let key = match self.key_wait {
+            KeyWait::Press(held) => {
+                let new = (keypad.down() | keypad.pressed()) & !held;
+                match new.trailing_zeros() as u8 {
+                    16 => None,
+                    key if self.config.quirks.key_release => {
+                        self.key_wait = KeyWait::Release(key);
+                        None
+                    }
+                    key => Some(key),
+                }
+            }
+            KeyWait::Release(key) => Some(key).filter(|&key| !keypad.is_down(key as usize)),
+            KeyWait::Idle => None,
+        };
+        match key {
+            Some(key) => {
+                self.key_wait = KeyWait::Idle;
+                self.register[register as usize] = key;
+            }
+            None => self.counter -= INSTRUCTION_SIZE,
         }
         Ok(())
     }
 
     fn set_delay(&mut self, register: u8) -> Result<(), Error> {
-        self.timer.0 = self.register[register as usize];
+        self.delay = self.register[register as usize];
         Ok(())
     }
 
     fn set_sound(&mut self, register: u8) -> Result<(), Error> {
-        self.timer.1 = self.register[register as usize];
-        Ok(())
+        let was_beeping = self.sound != 0;
+        self.sound = self.register[register as usize];
+        match (was_beeping, self.sound != 0) {
+            (false, true) => self.io.start_beep(),
+            (true, false) => self.io.end_beep(),
+            _ => Ok(()),
+        }
     }
 
     fn add_index(&mut self, register: u8) -> Result<(), Error> {
@@ -277,30 +323,171 @@ where
     }
 
     fn get_font(&mut self, register: u8) -> Result<(), Error> {
-        self.index = self.register[register as usize] as u16 * FONT_SIZE;
+        self.index = (self.register[register as usize] & 0xF) as u16 * FONT_SIZE;
         Ok(())
     }
 
     fn as_decimal(&mut self, register: u8) -> Result<(), Error> {
         let mut val = self.register[register as usize];
-        for i in 0..3 {
-            self.memory[self.index as usize + i] = val % 10;
-            val = val / 10;
+        let range = memory_range(self.index, 3)?;
+        self.written(range.clone());
+        for byte in self.memory[range].iter_mut().rev() {
+            *byte = val % 10;
+            val /= 10;
         }
         Ok(())
     }
 
     fn save(&mut self, register: u8) -> Result<(), Error> {
-        for i in 0..=register as usize {
-            self.memory[self.index as usize + i] = self.register[i];
-        }
+        let len = register as usize + 1;
+        let range = memory_range(self.index, len)?;
+        self.written(range.clone());
+        self.memory[range].copy_from_slice(&self.register[..len]);
+        self.increment_index(len);
         Ok(())
     }
 
     fn load(&mut self, register: u8) -> Result<(), Error> {
-        for i in 0..=register as usize {
-            self.register[i] = self.memory[self.index as usize + i];
-        }
+        let len = register as usize + 1;
+        self.register[..len].copy_from_slice(&self.memory[memory_range(self.index, len)?]);
+        self.increment_index(len);
         Ok(())
     }
+
+    fn damage_all(&mut self) {
+        self.damage
+            .add(Rect::new(0, 0, self.screen.width(), self.screen.height()));
+    }
+
+    // VF after a logic operation, on interpreters that clobber it.
+    fn reset_vf(&mut self) {
+        if self.config.quirks.vf_reset {
+            self.register[VF] = 0;
+        }
+    }
+
+    // I after a bulk register save or load, on interpreters that advance it.
+    fn increment_index(&mut self, len: usize) {
+        if self.config.quirks.memory_increment {
+            self.index = self.index.wrapping_add(len as u16);
+        }
+    }
+}
+
+// The on-screen parts of a line `len` pixels long, as (start, length).
+fn spans(
+    start: usize,
+    len: usize,
+    size: usize,
+    wrap: bool,
+) -> impl Iterator<Item = (usize, usize)> {
+    let visible = (size - start).min(len);
+    let wrapped = if wrap { len - visible } else { 0 };
+    [(start, visible), (0, wrapped.min(start))]
+        .into_iter()
+        .filter(|&(_, len)| len > 0)
+}
+
+// The `len` bytes of memory starting at `address`, if they are all in bounds.
+fn memory_range(address: u16, len: usize) -> Result<Range<usize>, Error> {
+    let start = address as usize;
+    if start + len > MEM_SIZE {
+        return Err(Error::MemoryOutOfBounds { address, len });
+    }
+    Ok(start..start + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChipConfig, ChipOxide, MockIO, Quirks, Rect};
+
+    // Draw an 8x8 block with its top left corner at (60, 28).
+    fn draw_at_corner(wrap: bool) -> MockIO {
+        let program = [
+            0x60, 0x3C, 0x61, 0x1C, 0xA2, 0x0A, 0xD0, 0x18, 0x12, 0x08, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF, 0xFF,
+        ];
+        let config = ChipConfig {
+            quirks: Quirks {
+                wrap,
+                ..Quirks::SCHIP
+            },
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        ChipOxide::new(&program, &mut io, &config)
+            .unwrap()
+            .cycle()
+            .unwrap();
+        io
+    }
+
+    #[test]
+    fn clips_at_the_edges() {
+        let io = draw_at_corner(false);
+        let frame = &io.frames()[0];
+        assert!(frame.screen.get(63, 31));
+        assert!(!frame.screen.get(0, 0));
+        assert_eq!(frame.screen.rows().flatten().filter(|&on| on).count(), 16);
+        assert_eq!(frame.damage, [Rect::new(60, 28, 4, 4)]);
+    }
+
+    #[test]
+    fn wraps_at_the_edges() {
+        let io = draw_at_corner(true);
+        let frame = &io.frames()[0];
+        assert!(frame.screen.get(63, 31));
+        assert!(frame.screen.get(0, 0));
+        assert!(frame.screen.get(3, 3));
+        assert!(!frame.screen.get(4, 4));
+        assert_eq!(frame.screen.rows().flatten().filter(|&on| on).count(), 64);
+        assert_eq!(frame.damage.len(), 4);
+    }
+
+    #[test]
+    fn held_keys_keep_registering() {
+        // V0 = 5, then loop: skip unless key V0 is up, V1 += 1.
+        let program = [0x60, 0x05, 0xE0, 0xA1, 0x71, 0x01, 0x12, 0x02];
+        let config = ChipConfig::default(false);
+        let mut io = MockIO::new();
+        io.press(1, 5);
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        for _ in 0..3 {
+            chip8.cycle().unwrap();
+        }
+        assert_eq!(chip8.register[1], 8);
+    }
+
+    // Wait for a key with FX0A while key 3 is held throughout and key 7 is
+    // pressed on cycle 2 and released on cycle 4. Returns the key and the
+    // cycle the wait finished on.
+    fn wait_for_key(key_release: bool) -> (u8, u64) {
+        let program = [0xF0, 0x0A, 0x12, 0x02];
+        let config = ChipConfig {
+            quirks: Quirks {
+                key_release,
+                ..Quirks::SCHIP
+            },
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        io.press(1, 3).press(2, 7).release(4, 7);
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        let mut cycle = 0;
+        while chip8.counter == 0x200 {
+            chip8.cycle().unwrap();
+            cycle += 1;
+        }
+        (chip8.register[0], cycle)
+    }
+
+    #[test]
+    fn key_wait_ignores_keys_already_down() {
+        assert_eq!(wait_for_key(false), (7, 2));
+    }
+
+    #[test]
+    fn key_wait_can_wait_for_release() {
+        assert_eq!(wait_for_key(true), (7, 4));
+    }
 }
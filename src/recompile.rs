@@ -0,0 +1,463 @@
+use crate::{
+    Analysis, ChipIO, ChipOxide, Error, Instruction, Quirks, Timing, COUNTER_START,
+    INSTRUCTION_SIZE, MEM_SIZE, REGISTER_SIZE,
+};
+use alloc::{format, string::String, vec, vec::Vec};
+use sha1_smol::Sha1;
+
+/// A basic block compiled ahead of time by `recompile`.
+pub struct Block<I: ChipIO> {
+    /// Where the block starts.
+    pub address: u16,
+    /// The opcodes it was compiled from. The block only runs while memory
+    /// still holds them, so overwritten code goes back to the interpreter.
+    pub code: &'static [u8],
+    /// Runs the whole block.
+    pub run: fn(&mut ChipOxide<'_, I>) -> Result<(), Error>,
+}
+
+impl<I: ChipIO> Block<I> {
+    /// The number of instructions in the block.
+    pub fn len(&self) -> usize {
+        self.code.len() / INSTRUCTION_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+}
+
+/// The machine state compiled blocks work on directly, from
+/// `ChipOxide::parts`.
+pub struct Parts<'m> {
+    /// V0 to VF.
+    pub v: &'m mut [u8; REGISTER_SIZE],
+    pub index: &'m mut u16,
+    /// Where to go after the block. Straight-line code only sets it at the
+    /// end.
+    pub counter: &'m mut usize,
+    /// The delay timer, which only changes between frames.
+    pub delay: u8,
+    pub quirks: Quirks,
+}
+
+/// The blocks of a recompiled ROM, looked up by address.
+pub struct Recompiled<I: ChipIO> {
+    blocks: Vec<Block<I>>,
+    // One more than the index of the block starting at each address, or 0.
+    index: Vec<u16>,
+}
+
+impl<I: ChipIO> Recompiled<I> {
+    /// Index the blocks from a generated module's `blocks()`.
+    pub fn new(blocks: Vec<Block<I>>) -> Self {
+        let mut index = vec![0; MEM_SIZE];
+        for (i, block) in blocks.iter().enumerate() {
+            if let Some(entry) = index.get_mut(block.address as usize) {
+                *entry = i as u16 + 1;
+            }
+        }
+        Self { blocks, index }
+    }
+
+    /// Every block, in the order given.
+    pub fn blocks(&self) -> &[Block<I>] {
+        &self.blocks
+    }
+
+    fn get(&self, address: usize) -> Option<&Block<I>> {
+        match *self.index.get(address)? {
+            0 => None,
+            i => Some(&self.blocks[i as usize - 1]),
+        }
+    }
+}
+
+impl<'a, I> ChipOxide<'a, I>
+where
+    I: ChipIO,
+{
+    /// Run one timer cycle like `cycle`, running compiled blocks wherever
+    /// memory still matches them and interpreting everything else. Blocks
+    /// never cross the end of a frame, so the result is the same as
+    /// `cycle`. VIP timing is always interpreted.
+    pub fn cycle_recompiled(&mut self, code: &Recompiled<I>) -> Result<(), Error> {
+        if self.config.timing != Timing::Fixed {
+            return self.cycle();
+        }
+        self.begin_frame()?;
+        let mut due = self.fixed_frame_due() as usize;
+        while due > 0 && !self.exited {
+            let address = self.counter;
+            let last = match code.get(address) {
+                Some(block)
+                    if block.len() <= due && self.memory[address..].starts_with(block.code) =>
+                {
+                    (block.run)(self)?;
+                    due -= block.len();
+                    address + block.code.len() - INSTRUCTION_SIZE
+                }
+                _ => {
+                    self.step()?;
+                    due -= 1;
+                    address
+                }
+            };
+            // Only the last instruction of a block can start an idle loop.
+            if self.idling(last) {
+                break;
+            }
+        }
+        self.present()
+    }
+
+    /// The registers, I and the counter, for compiled blocks to run simple
+    /// instructions on without going through `execute_at`.
+    #[inline]
+    pub fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            v: &mut self.register,
+            index: &mut self.index,
+            counter: &mut self.counter,
+            delay: self.delay,
+            quirks: self.config.quirks,
+        }
+    }
+
+    /// Execute an instruction as if it had just been fetched from `address`.
+    /// Compiled blocks use this for what they do not run themselves.
+    #[inline]
+    pub fn execute_at(&mut self, address: u16, inst: Instruction) -> Result<(), Error> {
+        self.counter = address as usize + INSTRUCTION_SIZE;
+        self.execute_instruction(inst)
+    }
+}
+
+/// Generate a Rust module running a ROM's reachable code, one function per
+/// basic block. The module's `blocks()` goes to `Recompiled::new`. Code only
+/// reached through BXNN, or written at run time, is left to the interpreter.
+/// Register, arithmetic, skip, jump and I instructions become plain Rust,
+/// and the rest calls `execute_at`.
+pub fn recompile(rom: &[u8]) -> String {
+    let blocks = split(rom);
+    let interpreted = blocks
+        .iter()
+        .flat_map(|(address, insts)| {
+            insts
+                .iter()
+                .enumerate()
+                .map(move |(i, inst)| native(address + i * INSTRUCTION_SIZE, *inst))
+        })
+        .any(|code| code.is_none());
+    let mut out = format!(
+        "// Recompiled by chip_oxide from a {} byte ROM with SHA-1 {}.\n\
+         // Do not edit; regenerate it instead.\n\n\
+         use chip_oxide::{{Block, ChipIO, ChipOxide, Error{}}};\n\n\
+         /// Every compiled block, in address order.\n\
+         pub fn blocks<I: ChipIO>() -> Vec<Block<I>> {{\n    vec![\n",
+        rom.len(),
+        Sha1::from(rom).digest(),
+        if interpreted { ", Instruction::*" } else { "" }
+    );
+    for (address, insts) in &blocks {
+        let code: Vec<String> = rom[address - COUNTER_START..][..insts.len() * INSTRUCTION_SIZE]
+            .iter()
+            .map(|byte| format!("0x{:02X}", byte))
+            .collect();
+        out += &format!(
+            "        Block {{ address: 0x{:03X}, code: &[{}], run: block_{:03x} }},\n",
+            address,
+            code.join(", "),
+            address
+        );
+    }
+    out += "    ]\n}\n";
+    for (address, insts) in &blocks {
+        out += &format!(
+            "\nfn block_{:03x}<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {{\n",
+            address
+        );
+        // Whether `p` holds the parts of `m`, since the last `execute_at`.
+        let mut parts = false;
+        for (i, &inst) in insts.iter().enumerate() {
+            let address = address + i * INSTRUCTION_SIZE;
+            let last = i + 1 == insts.len();
+            match native(address, inst) {
+                Some(code) => {
+                    if !parts {
+                        out += "    let p = m.parts();\n";
+                        parts = true;
+                    }
+                    out += &code;
+                    if last {
+                        if !sets_counter(&inst) {
+                            out += &format!(
+                                "    *p.counter = 0x{:03X};\n",
+                                address + INSTRUCTION_SIZE
+                            );
+                        }
+                        out += "    Ok(())\n";
+                    }
+                }
+                None => {
+                    parts = false;
+                    let end = if last { "" } else { "?;" };
+                    out += &format!("    m.execute_at(0x{:03X}, {:?}){}\n", address, inst, end);
+                }
+            }
+        }
+        out += "}\n";
+    }
+    out
+}
+
+// Rust running an instruction on the parts of the machine in `p`, for the
+// instructions simple enough. Mirrors `opcodes.rs`, like the JIT's native
+// code.
+fn native(address: usize, inst: Instruction) -> Option<String> {
+    let (next, skip) = (address + INSTRUCTION_SIZE, address + 2 * INSTRUCTION_SIZE);
+    let skip_if = |cond: String| {
+        format!(
+            "    *p.counter = if {} {{ 0x{:03X} }} else {{ 0x{:03X} }};\n",
+            cond, skip, next
+        )
+    };
+    let shifted = |x: u8, y: u8| match x == y {
+        true => format!("    let vy = p.v[0x{:X}];\n", x),
+        false => format!(
+            "    let vy = if p.quirks.shift_vy {{ p.v[0x{:X}] }} else {{ p.v[0x{:X}] }};\n",
+            y, x
+        ),
+    };
+    let code = match inst {
+        Instruction::Jump(nnn) => format!("    *p.counter = 0x{:03X};\n", nnn),
+        Instruction::SkipED(x, nn) => skip_if(format!("p.v[0x{:X}] == 0x{:02X}", x, nn)),
+        Instruction::SkipNED(x, nn) => skip_if(format!("p.v[0x{:X}] != 0x{:02X}", x, nn)),
+        Instruction::SkipER(x, y) => skip_if(format!("p.v[0x{:X}] == p.v[0x{:X}]", x, y)),
+        Instruction::SkipNER(x, y) => skip_if(format!("p.v[0x{:X}] != p.v[0x{:X}]", x, y)),
+        Instruction::SetRegisterD(x, nn) => format!("    p.v[0x{:X}] = 0x{:02X};\n", x, nn),
+        Instruction::AddRegisterD(x, nn) => {
+            format!(
+                "    p.v[0x{:X}] = p.v[0x{:X}].wrapping_add(0x{:02X});\n",
+                x, x, nn
+            )
+        }
+        Instruction::SetRegisterR(x, y) => format!("    p.v[0x{:X}] = p.v[0x{:X}];\n", x, y),
+        Instruction::BinaryOR(x, y)
+        | Instruction::BinaryAND(x, y)
+        | Instruction::LogicalXOR(x, y) => {
+            let op = match inst {
+                Instruction::BinaryOR(..) => "|",
+                Instruction::BinaryAND(..) => "&",
+                _ => "^",
+            };
+            format!(
+                "    p.v[0x{:X}] {}= p.v[0x{:X}];\n    \
+                 if p.quirks.vf_reset {{\n        p.v[0xF] = 0;\n    }}\n",
+                x, op, y
+            )
+        }
+        Instruction::AddRegisterR(x, y) => format!(
+            "    let (vx, carry) = p.v[0x{:X}].overflowing_add(p.v[0x{:X}]);\n    \
+             (p.v[0x{:X}], p.v[0xF]) = (vx, carry as u8);\n",
+            x, y, x
+        ),
+        Instruction::SubtractXY(x, y) | Instruction::SubtractYX(x, y) => {
+            let (a, b) = match inst {
+                Instruction::SubtractXY(..) => (x, y),
+                _ => (y, x),
+            };
+            format!(
+                "    let (vx, borrow) = p.v[0x{:X}].overflowing_sub(p.v[0x{:X}]);\n    \
+                 (p.v[0x{:X}], p.v[0xF]) = (vx, !borrow as u8);\n",
+                a, b, x
+            )
+        }
+        Instruction::ShiftRight(x, y) => format!(
+            "{}    (p.v[0x{:X}], p.v[0xF]) = (vy >> 1, vy & 1);\n",
+            shifted(x, y),
+            x
+        ),
+        Instruction::ShiftLeft(x, y) => format!(
+            "{}    (p.v[0x{:X}], p.v[0xF]) = (vy << 1, vy >> 7);\n",
+            shifted(x, y),
+            x
+        ),
+        Instruction::SetIndex(nnn) => format!("    *p.index = 0x{:03X};\n", nnn),
+        Instruction::AddIndex(x) => {
+            format!(
+                "    *p.index = p.index.wrapping_add(p.v[0x{:X}] as u16);\n",
+                x
+            )
+        }
+        Instruction::GetDelay(x) => format!("    p.v[0x{:X}] = p.delay;\n", x),
+        _ => return None,
+    };
+    Some(code)
+}
+
+// Whether the native code for an instruction sets the counter itself.
+fn sets_counter(inst: &Instruction) -> bool {
+    matches!(
+        inst,
+        Instruction::Jump(_)
+            | Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..)
+    )
+}
+
+// Split the reachable code into basic blocks, each with its address.
+fn split(rom: &[u8]) -> Vec<(usize, Vec<Instruction>)> {
+    let decode = |address: usize| {
+        let bytes = rom
+            .get(address.checked_sub(COUNTER_START)?..)?
+            .get(..INSTRUCTION_SIZE)?;
+        Instruction::try_from((bytes[0] as u16) << 8 | bytes[1] as u16).ok()
+    };
+    let mut reachable = vec![false; MEM_SIZE + INSTRUCTION_SIZE];
+    for &address in Analysis::of(rom).reachable() {
+        reachable[address as usize] = true;
+    }
+    // Blocks start at branch targets, after anything that ends a block, and
+    // wherever code is not reached by falling through.
+    let mut leaders = vec![false; MEM_SIZE + 2 * INSTRUCTION_SIZE];
+    for address in (0..MEM_SIZE).filter(|&address| reachable[address]) {
+        let Some(inst) = decode(address) else {
+            continue;
+        };
+        let next = address + INSTRUCTION_SIZE;
+        match inst {
+            Instruction::Jump(nnn) | Instruction::SubRoutine(nnn) => leaders[nnn as usize] = true,
+            Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..)
+            | Instruction::KeyPressed(_)
+            | Instruction::KeyReleased(_) => leaders[next + INSTRUCTION_SIZE] = true,
+            _ => {}
+        }
+        leaders[next] |= ends_block(&inst);
+        let falls_in = address >= INSTRUCTION_SIZE
+            && reachable[address - INSTRUCTION_SIZE]
+            && decode(address - INSTRUCTION_SIZE).is_some_and(|inst| !ends_block(&inst));
+        leaders[address] |= !falls_in;
+    }
+
+    let mut blocks = vec![];
+    for start in (0..MEM_SIZE).filter(|&address| reachable[address] && leaders[address]) {
+        let mut insts = vec![];
+        let mut address = start;
+        while let Some(inst) = decode(address) {
+            insts.push(inst);
+            address += INSTRUCTION_SIZE;
+            if ends_block(&inst) || leaders[address] || !reachable[address] {
+                break;
+            }
+        }
+        if !insts.is_empty() {
+            blocks.push((start, insts));
+        }
+    }
+    blocks
+}
+
+// Whether a block has to end after this instruction: control can leave the
+// straight line, the frame can end there because the machine is waiting,
+// or memory is written and the code after it may have changed.
+pub(crate) fn ends_block(inst: &Instruction) -> bool {
+    matches!(
+        inst,
+        Instruction::Return
+            | Instruction::Exit
+            | Instruction::Jump(_)
+            | Instruction::SubRoutine(_)
+            | Instruction::OffsetJump(..)
+            | Instruction::SkipED(..)
+            | Instruction::SkipNED(..)
+            | Instruction::SkipER(..)
+            | Instruction::SkipNER(..)
+            | Instruction::KeyPressed(_)
+            | Instruction::KeyReleased(_)
+            | Instruction::KeyWait(_)
+            | Instruction::GetDelay(_)
+            | Instruction::AsDecimal(_)
+            | Instruction::Save(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChipConfig, Instruction::*, MockIO};
+
+    #[test]
+    fn splits_at_branches_and_targets() {
+        // V0 = 5, then loop: V0 += 1, skip if V0 is 10, jump back; then spin.
+        let rom = [0x60, 0x05, 0x70, 0x01, 0x30, 0x0A, 0x12, 0x02, 0x12, 0x08];
+        assert_eq!(
+            split(&rom),
+            [
+                (0x200, vec![SetRegisterD(0, 5)]),
+                (0x202, vec![AddRegisterD(0, 1), SkipED(0, 10)]),
+                (0x206, vec![Jump(0x202)]),
+                (0x208, vec![Jump(0x208)]),
+            ]
+        );
+        let source = recompile(&rom);
+        assert!(source.contains("fn block_202<I: ChipIO>"));
+        assert!(source.contains(
+            "    let p = m.parts();\n    \
+             p.v[0x0] = p.v[0x0].wrapping_add(0x01);\n    \
+             *p.counter = if p.v[0x0] == 0x0A { 0x208 } else { 0x206 };\n    \
+             Ok(())\n"
+        ));
+    }
+
+    // The blocks `recompile` makes for the program in `overwritten_code_is_
+    // interpreted`, written out by hand.
+    fn block_200<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+        let p = m.parts();
+        p.v[0x1] = p.v[0x1].wrapping_add(0x01);
+        *p.index = 0x201;
+        p.v[0x0] = 0x05;
+        m.execute_at(0x206, Save(0))
+    }
+
+    fn block_208<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+        let p = m.parts();
+        *p.counter = 0x200;
+        Ok(())
+    }
+
+    #[test]
+    fn overwritten_code_is_interpreted() {
+        // V1 += 1, then overwrite its low byte with 5 and loop back to it.
+        let program = [0x71, 0x01, 0xA2, 0x01, 0x60, 0x05, 0xF0, 0x55, 0x12, 0x00];
+        let source = recompile(&program);
+        assert!(source.contains("fn block_200") && source.contains("fn block_208"));
+        let code = Recompiled::new(vec![
+            Block {
+                address: 0x200,
+                code: &[0x71, 0x01, 0xA2, 0x01, 0x60, 0x05, 0xF0, 0x55],
+                run: block_200,
+            },
+            Block {
+                address: 0x208,
+                code: &[0x12, 0x00],
+                run: block_208,
+            },
+        ]);
+        let config = ChipConfig {
+            instructions_per_second: 60 * 5,
+            ..ChipConfig::default(false)
+        };
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle_recompiled(&code).unwrap();
+        assert_eq!(chip8.registers()[1], 1);
+        chip8.cycle_recompiled(&code).unwrap();
+        assert_eq!(chip8.registers()[1], 6);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{FrameBlender, Framebuffer};
+use alloc::{vec, vec::Vec};
+
+/// Colours for each pixel value, as `0xRRGGBB`.
+///
+/// A pixel's value has bit `n` set when plane `n` is lit, so a one-plane
+/// display only uses the first two entries and XO-CHIP uses all four.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: [u32; 4],
+}
+
+impl Palette {
+    /// White on black, with the XO-CHIP planes in shades of grey.
+    pub const MONOCHROME: Self = Self {
+        colors: [0x000000, 0xFFFFFF, 0xAAAAAA, 0x555555],
+    };
+
+    /// Octo's default four colour palette.
+    pub const OCTO: Self = Self {
+        colors: [0x996600, 0xFFCC00, 0xFF6600, 0x662200],
+    };
+
+    /// A palette from a background and foreground colour, as the ROM
+    /// database stores them.
+    pub fn from_pair([background, foreground]: [u32; 2]) -> Self {
+        Self {
+            colors: [background, foreground, foreground, foreground],
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::MONOCHROME
+    }
+}
+
+/// An RGBA8 image, row-major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// The colour of a pixel as `[r, g, b, a]`.
+    pub fn get(&self, x: usize, y: usize) -> [u8; 4] {
+        let at = (y * self.width + x) * 4;
+        [
+            self.pixels[at],
+            self.pixels[at + 1],
+            self.pixels[at + 2],
+            self.pixels[at + 3],
+        ]
+    }
+}
+
+/// Turns the framebuffer into an RGBA8 image on the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renderer {
+    /// Size of a CHIP-8 pixel on the image, in image pixels. 0 counts as 1.
+    pub scale: usize,
+    pub palette: Palette,
+    /// Colour of the lines drawn between CHIP-8 pixels, if any. Needs a
+    /// scale of at least 2.
+    pub grid: Option<u32>,
+    /// Brightness of every other image row, out of 255, if any.
+    pub scanlines: Option<u8>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            palette: Palette::default(),
+            grid: None,
+            scanlines: None,
+        }
+    }
+}
+
+impl Renderer {
+    /// A plain renderer at the given scale.
+    pub fn new(scale: usize) -> Self {
+        Self {
+            scale: scale.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Size of the image for a screen, in pixels.
+    pub fn size(&self, screen: &Framebuffer) -> (usize, usize) {
+        let scale = self.scale();
+        (screen.width() * scale, screen.height() * scale)
+    }
+
+    pub(crate) fn scale(&self) -> usize {
+        self.scale.max(1)
+    }
+
+    /// Render a screen into a new image.
+    pub fn render(&self, screen: &Framebuffer) -> Image {
+        self.image(screen, |pixels| self.render_into(screen, pixels))
+    }
+
+    /// Render a screen after anti-flicker blending into a new image.
+    pub fn render_blended(&self, screen: &Framebuffer, blender: &FrameBlender) -> Image {
+        self.image(screen, |pixels| {
+            self.render_blended_into(screen, blender, pixels)
+        })
+    }
+
+    /// Render a screen into a buffer of `size()` RGBA8 pixels.
+    ///
+    /// # Panics
+    /// If the buffer is the wrong size.
+    pub fn render_into(&self, screen: &Framebuffer, pixels: &mut [u8]) {
+        self.render_with(screen, pixels, |x, y| {
+            self.palette.colors[screen.pixel(x, y) as usize]
+        })
+    }
+
+    /// Render a screen after anti-flicker blending. Each pixel is mixed
+    /// between the background and its colour by its blended brightness,
+    /// with unlit pixels fading in the first plane's colour.
+    ///
+    /// # Panics
+    /// If the buffer is the wrong size.
+    pub fn render_blended_into(
+        &self,
+        screen: &Framebuffer,
+        blender: &FrameBlender,
+        pixels: &mut [u8],
+    ) {
+        let colors = self.palette.colors;
+        self.render_with(screen, pixels, |x, y| {
+            let index = screen.pixel(x, y).max(1) as usize;
+            mix(colors[0], colors[index], blender.intensity(x, y))
+        })
+    }
+
+    fn image(&self, screen: &Framebuffer, fill: impl FnOnce(&mut [u8])) -> Image {
+        let (width, height) = self.size(screen);
+        let mut pixels = vec![0; width * height * 4];
+        fill(&mut pixels);
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Render with the colour of each CHIP-8 pixel coming from `color`.
+    fn render_with(
+        &self,
+        screen: &Framebuffer,
+        pixels: &mut [u8],
+        color: impl Fn(usize, usize) -> u32,
+    ) {
+        let (width, height) = self.size(screen);
+        assert_eq!(pixels.len(), width * height * 4, "wrong buffer size");
+        let scale = self.scale();
+        for (y, row) in pixels.chunks_exact_mut(width * 4).enumerate() {
+            for (x, out) in row.chunks_exact_mut(4).enumerate() {
+                let on_grid = scale > 1 && (x % scale == scale - 1 || y % scale == scale - 1);
+                let mut rgb = match self.grid {
+                    Some(grid) if on_grid => grid,
+                    _ => color(x / scale, y / scale),
+                };
+                if let Some(level) = self.scanlines.filter(|_| y % 2 == 1) {
+                    rgb = dim(rgb, level);
+                }
+                out.copy_from_slice(&[(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 0xFF]);
+            }
+        }
+    }
+}
+
+// Scale each channel of a colour by `level / 255`.
+fn dim(rgb: u32, level: u8) -> u32 {
+    [16, 8, 0].iter().fold(0, |out, shift| {
+        let channel = (rgb >> shift) & 0xFF;
+        out | (channel * level as u32 / 255) << shift
+    })
+}
+
+// Blend from one colour to another, `level / 255` of the way.
+fn mix(from: u32, to: u32, level: u8) -> u32 {
+    [16, 8, 0].iter().fold(0, |out, shift| {
+        let (a, b) = ((from >> shift) & 0xFF, (to >> shift) & 0xFF);
+        let channel = (a * (255 - level as u32) + b * level as u32) / 255;
+        out | channel << shift
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Blend, Resolution};
+
+    #[test]
+    fn scales_pixels_into_blocks() {
+        let mut screen = Framebuffer::default();
+        screen.set(0, 1, 0, true);
+        let image = Renderer::new(3).render(&screen);
+        assert_eq!((image.width, image.height), (192, 96));
+        assert_eq!(image.get(2, 2), [0, 0, 0, 0xFF]);
+        assert_eq!(image.get(3, 0), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(image.get(5, 2), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(image.get(6, 0), [0, 0, 0, 0xFF]);
+    }
+
+    #[test]
+    fn zero_scale_renders_at_one() {
+        let renderer = Renderer {
+            scale: 0,
+            ..Renderer::default()
+        };
+        let screen = Framebuffer::default();
+        assert_eq!(renderer.render(&screen), Renderer::new(1).render(&screen));
+    }
+
+    #[test]
+    fn colours_planes_from_the_palette() {
+        let mut screen = Framebuffer::new(Resolution::High, 2);
+        screen.set(1, 0, 0, true);
+        screen.set(0, 1, 0, true);
+        screen.set(1, 1, 0, true);
+        let renderer = Renderer {
+            palette: Palette::OCTO,
+            ..Renderer::default()
+        };
+        let image = renderer.render(&screen);
+        assert_eq!(image.get(0, 0), [0xFF, 0x66, 0x00, 0xFF]);
+        assert_eq!(image.get(1, 0), [0x66, 0x22, 0x00, 0xFF]);
+        assert_eq!(image.get(2, 0), [0x99, 0x66, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn draws_grid_and_scanlines() {
+        let mut screen = Framebuffer::default();
+        screen.set(0, 0, 0, true);
+        let renderer = Renderer {
+            grid: Some(0x202020),
+            scanlines: Some(128),
+            ..Renderer::new(4)
+        };
+        let image = renderer.render(&screen);
+        assert_eq!(image.get(0, 0), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(image.get(0, 1), [0x80, 0x80, 0x80, 0xFF]);
+        assert_eq!(image.get(3, 0), [0x20, 0x20, 0x20, 0xFF]);
+        assert_eq!(image.get(0, 3), [0x10, 0x10, 0x10, 0xFF]);
+    }
+
+    #[test]
+    fn fades_blended_pixels() {
+        let mut screen = Framebuffer::default();
+        let mut blender = FrameBlender::new(Blend::Phosphor { persistence: 51 });
+        screen.set(0, 0, 0, true);
+        blender.push(&screen);
+        screen.clear();
+        blender.push(&screen);
+        let image = Renderer::default().render_blended(&screen, &blender);
+        assert_eq!(image.get(0, 0), [0x33, 0x33, 0x33, 0xFF]);
+        assert_eq!(image.get(1, 0), [0, 0, 0, 0xFF]);
+    }
+}
@@ -0,0 +1,56 @@
+/// Random numbers for CXNN. SplitMix64, which is small, fast and needs
+/// nothing from the platform.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Start from `seed`, or from a different seed each run if there is
+    /// none and `std` can provide one.
+    pub(crate) fn new(seed: Option<u64>) -> Self {
+        Self {
+            state: seed.unwrap_or_else(fresh_seed),
+        }
+    }
+
+    pub(crate) fn next_u8(&mut self) -> u8 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) as u8
+    }
+}
+
+// Hashers are randomly keyed per process, which is all the entropy needed.
+#[cfg(feature = "std")]
+fn fresh_seed() -> u64 {
+    use std::hash::BuildHasher;
+    std::collections::hash_map::RandomState::new().hash_one(0u8)
+}
+
+#[cfg(not(feature = "std"))]
+fn fresh_seed() -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_repeat() {
+        let (mut a, mut b) = (Rng::new(Some(7)), Rng::new(Some(7)));
+        let a: Vec<u8> = (0..64).map(|_| a.next_u8()).collect();
+        let b: Vec<u8> = (0..64).map(|_| b.next_u8()).collect();
+        assert_eq!(a, b);
+        // Every value turns up eventually.
+        let mut rng = Rng::new(Some(0));
+        let mut seen = [false; 256];
+        for _ in 0..4096 {
+            seen[rng.next_u8() as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
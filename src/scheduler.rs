@@ -0,0 +1,146 @@
+use crate::Clock;
+use core::time::Duration;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+// One frame in the accumulator, which also counts in hundredths for speed.
+const FRAME: u128 = NANOS_PER_SECOND * 100;
+// Frames run at once to catch up. After a longer stall, such as the host
+// being suspended, the rest of the backlog is dropped.
+const MAX_CATCH_UP: u32 = 4;
+
+/// Runs frames at a fixed rate from a `Clock`, without drift.
+///
+/// Elapsed time is kept in an exact accumulator of `nanoseconds * hz *
+/// speed`, so a 60 Hz schedule at normal speed runs exactly 60 frames each
+/// second however the calls to `advance` fall.
+#[derive(Debug, Clone)]
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    hz: u32,
+    // Percent of real time.
+    speed: u32,
+    last: Duration,
+    accumulator: u128,
+}
+
+impl<C: Clock> Scheduler<C> {
+    /// A scheduler running `hz` frames a second, starting now.
+    pub fn new(clock: C, hz: u32) -> Self {
+        let last = clock.now();
+        Self {
+            clock,
+            hz: hz.max(1),
+            speed: 100,
+            last,
+            accumulator: 0,
+        }
+    }
+
+    pub fn hz(&self) -> u32 {
+        self.hz
+    }
+
+    /// Speed as a percentage of real time.
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    /// Run faster or slower than real time, 100 being normal speed.
+    pub fn set_speed(&mut self, percent: u32) {
+        self.speed = percent.max(1);
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Take the time passed since the last call into account, returning how
+    /// many frames are now due.
+    pub fn advance(&mut self) -> u32 {
+        let now = self.clock.now();
+        self.accumulator += (now - self.last).as_nanos() * self.rate();
+        self.last = now;
+        let frames = self.accumulator / FRAME;
+        self.accumulator %= FRAME;
+        let most = MAX_CATCH_UP as u128 * self.speed.div_ceil(100) as u128;
+        frames.min(most) as u32
+    }
+
+    /// Time left until the next frame is due.
+    pub fn until_next(&self) -> Duration {
+        let left = (FRAME - self.accumulator).div_ceil(self.rate());
+        let passed = (self.clock.now() - self.last).as_nanos();
+        Duration::from_nanos(left.saturating_sub(passed) as u64)
+    }
+
+    /// Sleep until the next frame is due.
+    pub fn wait(&mut self) {
+        let left = self.until_next();
+        self.clock.sleep(left);
+    }
+
+    // Accumulator units per nanosecond.
+    fn rate(&self) -> u128 {
+        self.hz as u128 * self.speed as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    #[test]
+    fn runs_exactly_hz_frames_a_second() {
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        let mut frames = 0;
+        for _ in 0..1000 {
+            scheduler.clock_mut().advance(Duration::from_millis(1));
+            frames += scheduler.advance();
+        }
+        assert_eq!(frames, 60);
+    }
+
+    #[test]
+    fn sleeps_until_the_next_frame() {
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        let mut frames = 0;
+        for _ in 0..600 {
+            scheduler.wait();
+            frames += scheduler.advance();
+        }
+        assert_eq!(frames, 600);
+        // Sleeps round up to whole nanoseconds, but never drift further.
+        let late = scheduler.clock().now() - Duration::from_secs(10);
+        assert!(late < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn speed_scales_time() {
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        let mut frames = 0;
+        scheduler.set_speed(250);
+        for _ in 0..100 {
+            scheduler.clock_mut().advance(Duration::from_millis(10));
+            frames += scheduler.advance();
+        }
+        scheduler.set_speed(50);
+        for _ in 0..100 {
+            scheduler.clock_mut().advance(Duration::from_millis(10));
+            frames += scheduler.advance();
+        }
+        assert_eq!(frames, 150 + 30);
+    }
+
+    #[test]
+    fn drops_long_stalls() {
+        let mut scheduler = Scheduler::new(ManualClock::new(), 60);
+        scheduler.clock_mut().advance(Duration::from_secs(5));
+        assert_eq!(scheduler.advance(), MAX_CATCH_UP);
+        assert_eq!(scheduler.advance(), 0);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{ChipIO, ChipOxide, Error, Instruction, INSTRUCTION_SIZE};
+
+// 1802 machine cycles in a 60 Hz frame, at 1.7609 MHz and 8 clocks a cycle.
+const VIP_FRAME_CYCLES: i64 = 3668;
+// Cycles taken each frame by display DMA, 8 bytes for each of 128 lines,
+// and the interrupt routine that sets it up and ticks the timers.
+const VIP_DISPLAY_CYCLES: i64 = 1024 + 32;
+// Fetching and dispatching any instruction.
+const VIP_OVERHEAD: u32 = 40;
+
+/// How long instructions take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timing {
+    /// Every instruction costs the same, running `instructions_per_second`.
+    #[default]
+    Fixed,
+    /// Instructions cost what they took on the COSMAC VIP interpreter, and
+    /// sprite drawing waits for the display interrupt, which ends the frame.
+    /// `instructions_per_second` is ignored.
+    Vip,
+}
+
+impl<'a, I> ChipOxide<'a, I>
+where
+    I: ChipIO,
+{
+    // Run a frame's worth of VIP machine cycles. Instructions running over
+    // the end of the frame eat into the next one.
+    pub(crate) fn run_vip_frame(&mut self) -> Result<(), Error> {
+        self.cycle_budget += VIP_FRAME_CYCLES - VIP_DISPLAY_CYCLES;
+        while self.cycle_budget > 0 && !self.exited {
+            let address = self.counter;
+            let inst = self.fetch_instruction()?;
+            self.execute_instruction(inst)?;
+            let skipped = self.counter == address + 2 * INSTRUCTION_SIZE;
+            self.cycle_budget -= self.vip_cycles(inst, skipped) as i64;
+            if self.idling(address) {
+                // Nothing changes until the interrupt, so there is nothing
+                // to owe the next frame.
+                self.cycle_budget = 0;
+                break;
+            }
+            if let Instruction::Draw(..) = inst {
+                // Drawing waits for the next interrupt, and whatever is
+                // left of the frame goes with it.
+                self.cycle_budget = self.cycle_budget.min(0);
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Machine cycles an instruction takes on the VIP: 40 to fetch and
+    // dispatch it, plus what its routine in the interpreter takes. The
+    // counts are the ones Jackson Sommerich tallied from the listing in
+    // "Chip-8 Instruction Scheduling and Frequency" (2019). DXYN is the one
+    // estimate: its routine's time depends on the sprite's bits and how
+    // they straddle display bytes, so it is charged an average per row.
+    fn vip_cycles(&self, inst: Instruction, skipped: bool) -> u32 {
+        let skip = if skipped { 4 } else { 0 };
+        VIP_OVERHEAD
+            + match inst {
+                Instruction::Clear => 3078,
+                Instruction::Return => 10,
+                Instruction::Jump(_) => 12,
+                Instruction::SubRoutine(_) => 26,
+                Instruction::SkipED(..) | Instruction::SkipNED(..) => 10 + skip,
+                Instruction::SkipER(..) | Instruction::SkipNER(..) => 14 + skip,
+                Instruction::SetRegisterD(..) => 6,
+                Instruction::AddRegisterD(..) => 10,
+                Instruction::SetRegisterR(..) => 12,
+                Instruction::BinaryOR(..)
+                | Instruction::BinaryAND(..)
+                | Instruction::LogicalXOR(..)
+                | Instruction::AddRegisterR(..)
+                | Instruction::SubtractXY(..)
+                | Instruction::ShiftRight(..)
+                | Instruction::SubtractYX(..)
+                | Instruction::ShiftLeft(..) => 44,
+                Instruction::SetIndex(_) => 12,
+                Instruction::OffsetJump(..) => 22,
+                Instruction::Random(..) => 36,
+                Instruction::Draw(_, _, n) => 26 + 46 * n as u32,
+                Instruction::KeyPressed(_) | Instruction::KeyReleased(_) => 14 + skip,
+                Instruction::GetDelay(_) | Instruction::SetDelay(_) | Instruction::SetSound(_) => {
+                    10
+                }
+                Instruction::KeyWait(_) => 20,
+                Instruction::AddIndex(_) | Instruction::GetFont(_) => 16,
+                Instruction::AsDecimal(x) => {
+                    let value = self.register[x as usize] as u32;
+                    let digits = value / 100 + value / 10 % 10 + value % 10;
+                    80 + 16 * digits
+                }
+                Instruction::Save(x) | Instruction::Load(x) => 14 + 14 * (x as u32 + 1),
+                // Not on the VIP.
+                Instruction::Exit | Instruction::LowRes | Instruction::HighRes => 0,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChipConfig, MockIO};
+
+    fn vip_config() -> ChipConfig {
+        ChipConfig {
+            timing: Timing::Vip,
+            ..ChipConfig::default(true)
+        }
+    }
+
+    #[test]
+    fn costs_vary_by_instruction() {
+        // V0 += 1, over and over: 50 cycles each.
+        let program = [0x70, 0x01].repeat(200);
+        let config = vip_config();
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.registers()[0], 53);
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.registers()[0], 105);
+    }
+
+    #[test]
+    fn instructions_take_their_listed_time() {
+        let config = vip_config();
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&[], &mut io, &config).unwrap();
+        chip8.register[3] = 123;
+        for (inst, skipped, cycles) in [
+            (Instruction::Return, false, 50),
+            (Instruction::Jump(0x200), false, 52),
+            (Instruction::SubRoutine(0x200), false, 66),
+            (Instruction::SkipED(0, 0), false, 50),
+            (Instruction::SkipED(0, 0), true, 54),
+            (Instruction::SkipER(0, 1), true, 58),
+            (Instruction::SetRegisterD(0, 1), false, 46),
+            (Instruction::AddRegisterD(0, 1), false, 50),
+            (Instruction::SetRegisterR(0, 1), false, 52),
+            (Instruction::AddRegisterR(0, 1), false, 84),
+            (Instruction::SetIndex(0x200), false, 52),
+            (Instruction::Random(0, 0xFF), false, 76),
+            (Instruction::AddIndex(0), false, 56),
+            // 1 + 2 + 3 digits to count out.
+            (Instruction::AsDecimal(3), false, 40 + 80 + 16 * 6),
+            // V0 to V2.
+            (Instruction::Save(2), false, 40 + 14 + 14 * 3),
+            (Instruction::Clear, false, 3118),
+        ] {
+            assert_eq!(chip8.vip_cycles(inst, skipped), cycles, "{:?}", inst);
+        }
+    }
+
+    #[test]
+    fn long_instructions_eat_into_the_next_frame() {
+        // Clear the screen twice, V0 = 1, then spin.
+        let program = [0x00, 0xE0, 0x00, 0xE0, 0x60, 0x01, 0x12, 0x06];
+        let config = vip_config();
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        chip8.cycle().unwrap();
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.counter(), 0x204);
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.registers()[0], 1);
+    }
+
+    #[test]
+    fn drawing_waits_for_the_interrupt() {
+        // Draw, V1 += 1, forever.
+        let program = [0xD0, 0x01, 0x71, 0x01, 0x12, 0x00];
+        let config = vip_config();
+        let mut io = MockIO::new();
+        let mut chip8 = ChipOxide::new(&program, &mut io, &config).unwrap();
+        for _ in 0..5 {
+            chip8.cycle().unwrap();
+        }
+        assert_eq!(chip8.registers()[1], 4);
+    }
+}
@@ -0,0 +1,45 @@
+// What the integration tests and benchmarks share. Not all of them use
+// everything.
+#![allow(dead_code)]
+
+use chip_oxide::{ChipConfig, ChipIO, ChipOxide, Error, Framebuffer, Rect};
+
+// Does nothing, so runs only differ by how instructions are executed.
+pub struct NullIO;
+
+impl ChipIO for NullIO {
+    fn present_frame(&mut self, _: &Framebuffer, _: &[Rect]) -> Result<(), Error> {
+        Ok(())
+    }
+    fn start_beep(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_beep(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Run a ROM interpreted with `config`, and with `other` advanced a frame at
+// a time by `cycle`, comparing the whole machine after every frame.
+pub fn matches_interpreter(
+    rom: &[u8],
+    config: &ChipConfig,
+    other: &ChipConfig,
+    mut cycle: impl FnMut(&mut ChipOxide<NullIO>) -> Result<(), Error>,
+) {
+    let (mut io, mut other_io) = (NullIO, NullIO);
+    let mut interpreted = ChipOxide::new(rom, &mut io, config).unwrap();
+    let mut compiled = ChipOxide::new(rom, &mut other_io, other).unwrap();
+    for frame in 0..300 {
+        interpreted.cycle().unwrap();
+        cycle(&mut compiled).unwrap();
+        assert_eq!(compiled.memory(), interpreted.memory(), "frame {}", frame);
+        assert_eq!(compiled.screen(), interpreted.screen(), "frame {}", frame);
+        assert_eq!(compiled.registers(), interpreted.registers());
+        assert_eq!(compiled.timers(), interpreted.timers());
+        assert_eq!(compiled.stack(), interpreted.stack());
+        assert_eq!(compiled.index(), interpreted.index());
+        assert_eq!(compiled.counter(), interpreted.counter(), "frame {}", frame);
+        assert_eq!(compiled.idle_frames(), interpreted.idle_frames());
+    }
+}
@@ -0,0 +1,16 @@
+#[path = "../examples/database.rs"]
+#[allow(dead_code)]
+mod database;
+
+#[test]
+fn generated_database_is_up_to_date() {
+    let source = database::generate(
+        include_str!("../data/roms.json"),
+        include_str!("../data/hints.json"),
+    )
+    .unwrap();
+    assert!(
+        source == include_str!("../src/database/roms.rs"),
+        "Regenerate with `cargo run --example database`"
+    );
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "jit")]
+
+use chip_oxide::{ChipConfig, Engine};
+
+mod common;
+
+// Run a ROM interpreted and compiled side by side.
+fn matches_interpreter(rom: &[u8], config: &ChipConfig) {
+    let jit = ChipConfig {
+        engine: Engine::Jit,
+        ..*config
+    };
+    common::matches_interpreter(rom, config, &jit, |chip8| chip8.cycle());
+}
+
+#[test]
+fn compiled_roms_match_the_interpreter() {
+    for ips in [60, 480, 60 * 1000] {
+        for legacy in [false, true] {
+            let config = ChipConfig {
+                instructions_per_second: ips,
+                ..ChipConfig::default(legacy)
+            };
+            matches_interpreter(include_bytes!("../roms/bc_test.ch8"), &config);
+            matches_interpreter(include_bytes!("../roms/IBM Logo.ch8"), &config);
+            matches_interpreter(include_bytes!("../roms/test_opcode.ch8"), &config);
+        }
+    }
+}
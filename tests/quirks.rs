@@ -0,0 +1,64 @@
+use chip_oxide::{ChipConfig, ChipOxide, Framebuffer, Platform, Quirks};
+use common::NullIO;
+use std::{env, fs};
+
+mod common;
+
+// The quirks test from Timendus' CHIP-8 test suite,
+// https://github.com/Timendus/chip8-test-suite.
+const ROM: &str = "roms/5-quirks.ch8";
+
+// The screen as text, one line per row.
+fn text(screen: &Framebuffer) -> String {
+    (0..screen.height())
+        .map(|y| {
+            (0..screen.width())
+                .map(|x| if screen.get(x, y) { '#' } else { '.' })
+                .chain(['\n'])
+                .collect::<String>()
+        })
+        .collect()
+}
+
+// Run the ROM under a platform's quirks and compare its results screen with
+// tests/quirks/<name>.txt. Set BLESS to write that file instead, after
+// checking every quirk is marked as passed.
+fn check(rom: &[u8], platform: Platform, choice: u8, name: &str) {
+    let config = ChipConfig {
+        quirks: Quirks::for_platform(platform),
+        instructions_per_second: 60 * 1000,
+        ..ChipConfig::default(false)
+    };
+    let mut io = NullIO;
+    let mut chip8 = ChipOxide::new(rom, &mut io, &config).unwrap();
+    // The test skips its menu when a launcher leaves the platform to test
+    // at 0x1FF.
+    chip8.write_memory(0x1FF, &[choice]).unwrap();
+    for _ in 0..600 {
+        chip8.cycle().unwrap();
+    }
+    let screen = text(chip8.screen());
+    let path = format!("tests/quirks/{}.txt", name);
+    if env::var_os("BLESS").is_some() {
+        fs::write(&path, screen).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("No {}, check the screen and run with BLESS=1", path));
+    assert!(screen == expected, "{} screen:\n{}", name, screen);
+}
+
+#[test]
+#[ignore = "needs roms/5-quirks.ch8 from the Timendus test suite"]
+fn passes_the_quirks_test() {
+    let rom = fs::read(ROM).unwrap_or_else(|_| panic!("Put the quirks test at {}", ROM));
+    // SUPER-CHIP is tested as the modern variant, without the legacy
+    // wait for the display.
+    for (platform, choice, name) in [
+        (Platform::Chip8, 1, "chip8"),
+        (Platform::SChip, 2, "schip"),
+        (Platform::XoChip, 3, "xochip"),
+    ] {
+        check(&rom, platform, choice, name);
+    }
+}
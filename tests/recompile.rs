@@ -0,0 +1,71 @@
+use chip_oxide::{recompile, ChipConfig, Recompiled};
+use common::NullIO;
+
+mod common;
+
+mod bc_test {
+    include!("recompiled/bc_test.rs");
+}
+mod ibm_logo {
+    include!("recompiled/ibm_logo.rs");
+}
+mod test_opcode {
+    include!("recompiled/test_opcode.rs");
+}
+
+#[test]
+fn generated_modules_are_up_to_date() {
+    for (rom, module) in [
+        (
+            &include_bytes!("../roms/bc_test.ch8")[..],
+            include_str!("recompiled/bc_test.rs"),
+        ),
+        (
+            include_bytes!("../roms/IBM Logo.ch8"),
+            include_str!("recompiled/ibm_logo.rs"),
+        ),
+        (
+            include_bytes!("../roms/test_opcode.ch8"),
+            include_str!("recompiled/test_opcode.rs"),
+        ),
+    ] {
+        assert!(
+            recompile(rom) == module,
+            "Regenerate with `cargo run --example recompile`"
+        );
+    }
+}
+
+// Run a ROM interpreted and recompiled side by side.
+fn matches_interpreter(rom: &[u8], code: Recompiled<NullIO>, config: &ChipConfig) {
+    common::matches_interpreter(rom, config, config, |chip8| chip8.cycle_recompiled(&code));
+}
+
+#[test]
+fn recompiled_roms_match_the_interpreter() {
+    // Both quirk sets, since compiled blocks check them as they run.
+    for (ips, legacy) in [60, 480, 60 * 1000]
+        .into_iter()
+        .flat_map(|ips| [(ips, false), (ips, true)])
+    {
+        let config = ChipConfig {
+            instructions_per_second: ips,
+            ..ChipConfig::default(legacy)
+        };
+        matches_interpreter(
+            include_bytes!("../roms/bc_test.ch8"),
+            Recompiled::new(bc_test::blocks()),
+            &config,
+        );
+        matches_interpreter(
+            include_bytes!("../roms/IBM Logo.ch8"),
+            Recompiled::new(ibm_logo::blocks()),
+            &config,
+        );
+        matches_interpreter(
+            include_bytes!("../roms/test_opcode.ch8"),
+            Recompiled::new(test_opcode::blocks()),
+            &config,
+        );
+    }
+}
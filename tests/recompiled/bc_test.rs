@@ -0,0 +1,513 @@
+// Recompiled by chip_oxide from a 470 byte ROM with SHA-1 9df1689015a0d1d95144f141903296f9f1c35fc5.
+// Do not edit; regenerate it instead.
+
+use chip_oxide::{Block, ChipIO, ChipOxide, Error, Instruction::*};
+
+/// Every compiled block, in address order.
+pub fn blocks<I: ChipIO>() -> Vec<Block<I>> {
+    vec![
+        Block { address: 0x200, code: &[0x00, 0xE0, 0x63, 0x00, 0x64, 0x01, 0x65, 0xEE, 0x35, 0xEE], run: block_200 },
+        Block { address: 0x20A, code: &[0x13, 0x10], run: block_20a },
+        Block { address: 0x20C, code: &[0x63, 0x00, 0x64, 0x02, 0x65, 0xEE, 0x66, 0xEE, 0x55, 0x60], run: block_20c },
+        Block { address: 0x216, code: &[0x13, 0x10], run: block_216 },
+        Block { address: 0x218, code: &[0x63, 0x00, 0x64, 0x03, 0x65, 0xEE, 0x45, 0xFD], run: block_218 },
+        Block { address: 0x220, code: &[0x13, 0x10], run: block_220 },
+        Block { address: 0x222, code: &[0x63, 0x00, 0x64, 0x04, 0x65, 0xEE, 0x75, 0x01, 0x35, 0xEF], run: block_222 },
+        Block { address: 0x22C, code: &[0x13, 0x10], run: block_22c },
+        Block { address: 0x22E, code: &[0x63, 0x00, 0x64, 0x05, 0x6F, 0x01, 0x65, 0xEE, 0x66, 0xEF, 0x85, 0x65, 0x3F, 0x00], run: block_22e },
+        Block { address: 0x23C, code: &[0x13, 0x10], run: block_23c },
+        Block { address: 0x23E, code: &[0x63, 0x00, 0x64, 0x06, 0x6F, 0x00, 0x65, 0xEF, 0x66, 0xEE, 0x85, 0x65, 0x3F, 0x01], run: block_23e },
+        Block { address: 0x24C, code: &[0x13, 0x10], run: block_24c },
+        Block { address: 0x24E, code: &[0x6F, 0x00, 0x63, 0x00, 0x64, 0x07, 0x65, 0xEE, 0x66, 0xEF, 0x85, 0x67, 0x3F, 0x01], run: block_24e },
+        Block { address: 0x25C, code: &[0x13, 0x10], run: block_25c },
+        Block { address: 0x25E, code: &[0x63, 0x00, 0x64, 0x08, 0x6F, 0x01, 0x65, 0xEF, 0x66, 0xEE, 0x85, 0x67, 0x3F, 0x00], run: block_25e },
+        Block { address: 0x26C, code: &[0x13, 0x10], run: block_26c },
+        Block { address: 0x26E, code: &[0x63, 0x00, 0x64, 0x09, 0x65, 0xF0, 0x66, 0x0F, 0x85, 0x61, 0x35, 0xFF], run: block_26e },
+        Block { address: 0x27A, code: &[0x13, 0x10], run: block_27a },
+        Block { address: 0x27C, code: &[0x63, 0x01, 0x64, 0x00, 0x65, 0xF0, 0x66, 0x0F, 0x85, 0x62, 0x35, 0x00], run: block_27c },
+        Block { address: 0x288, code: &[0x13, 0x10], run: block_288 },
+        Block { address: 0x28A, code: &[0x63, 0x01, 0x64, 0x01, 0x65, 0xF0, 0x66, 0x0F, 0x85, 0x63, 0x35, 0xFF], run: block_28a },
+        Block { address: 0x296, code: &[0x13, 0x10], run: block_296 },
+        Block { address: 0x298, code: &[0x6F, 0x00, 0x63, 0x01, 0x64, 0x02, 0x65, 0x81, 0x85, 0x0E, 0x3F, 0x01], run: block_298 },
+        Block { address: 0x2A4, code: &[0x13, 0x10], run: block_2a4 },
+        Block { address: 0x2A6, code: &[0x63, 0x01, 0x64, 0x03, 0x6F, 0x01, 0x65, 0x47, 0x85, 0x0E, 0x3F, 0x00], run: block_2a6 },
+        Block { address: 0x2B2, code: &[0x13, 0x10], run: block_2b2 },
+        Block { address: 0x2B4, code: &[0x63, 0x01, 0x64, 0x04, 0x6F, 0x00, 0x65, 0x01, 0x85, 0x06, 0x3F, 0x01], run: block_2b4 },
+        Block { address: 0x2C0, code: &[0x13, 0x10], run: block_2c0 },
+        Block { address: 0x2C2, code: &[0x63, 0x01, 0x64, 0x05, 0x6F, 0x01, 0x65, 0x02, 0x85, 0x06, 0x3F, 0x00], run: block_2c2 },
+        Block { address: 0x2CE, code: &[0x13, 0x10], run: block_2ce },
+        Block { address: 0x2D0, code: &[0x63, 0x01, 0x64, 0x06, 0x60, 0x15, 0x61, 0x78, 0xA3, 0xD0, 0xF1, 0x55], run: block_2d0 },
+        Block { address: 0x2DC, code: &[0xF1, 0x65, 0x30, 0x15], run: block_2dc },
+        Block { address: 0x2E0, code: &[0x13, 0x10], run: block_2e0 },
+        Block { address: 0x2E2, code: &[0x31, 0x78], run: block_2e2 },
+        Block { address: 0x2E4, code: &[0x13, 0x10], run: block_2e4 },
+        Block { address: 0x2E6, code: &[0x63, 0x01, 0x64, 0x07, 0x60, 0x8A, 0xA3, 0xD0, 0xF0, 0x33], run: block_2e6 },
+        Block { address: 0x2F0, code: &[0xA3, 0xD0, 0xF0, 0x65, 0x30, 0x01], run: block_2f0 },
+        Block { address: 0x2F6, code: &[0x13, 0x10], run: block_2f6 },
+        Block { address: 0x2F8, code: &[0x60, 0x01, 0xF0, 0x1E, 0xF0, 0x65, 0x30, 0x03], run: block_2f8 },
+        Block { address: 0x300, code: &[0x13, 0x10], run: block_300 },
+        Block { address: 0x302, code: &[0x60, 0x01, 0xF0, 0x1E, 0xF0, 0x65, 0x30, 0x08], run: block_302 },
+        Block { address: 0x30A, code: &[0x13, 0x10], run: block_30a },
+        Block { address: 0x30C, code: &[0x13, 0x32], run: block_30c },
+        Block { address: 0x30E, code: &[0x13, 0x0E], run: block_30e },
+        Block { address: 0x310, code: &[0xA3, 0x2A, 0x60, 0x13, 0x61, 0x09, 0xD0, 0x18, 0xF3, 0x29, 0x60, 0x22, 0x61, 0x0B, 0xD0, 0x15, 0xF4, 0x29, 0x60, 0x28, 0x61, 0x0B, 0xD0, 0x15, 0x13, 0x0E], run: block_310 },
+        Block { address: 0x332, code: &[0xA3, 0x58, 0x60, 0x15, 0x61, 0x0B, 0x63, 0x08], run: block_332 },
+        Block { address: 0x33A, code: &[0xD0, 0x18, 0x70, 0x08, 0xF3, 0x1E, 0x30, 0x2D], run: block_33a },
+        Block { address: 0x342, code: &[0x13, 0x3A], run: block_342 },
+        Block { address: 0x344, code: &[0xA3, 0x70, 0x60, 0x02, 0x61, 0x18, 0x63, 0x08], run: block_344 },
+        Block { address: 0x34C, code: &[0xD0, 0x18, 0x70, 0x05, 0xF3, 0x1E, 0x30, 0x3E], run: block_34c },
+        Block { address: 0x354, code: &[0x13, 0x4C], run: block_354 },
+        Block { address: 0x356, code: &[0x13, 0x0E], run: block_356 },
+    ]
+}
+
+fn block_200<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x200, Clear)?;
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x01;
+    p.v[0x5] = 0xEE;
+    *p.counter = if p.v[0x5] == 0xEE { 0x20C } else { 0x20A };
+    Ok(())
+}
+
+fn block_20a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_20c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x02;
+    p.v[0x5] = 0xEE;
+    p.v[0x6] = 0xEE;
+    *p.counter = if p.v[0x5] == p.v[0x6] { 0x218 } else { 0x216 };
+    Ok(())
+}
+
+fn block_216<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_218<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x03;
+    p.v[0x5] = 0xEE;
+    *p.counter = if p.v[0x5] != 0xFD { 0x222 } else { 0x220 };
+    Ok(())
+}
+
+fn block_220<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_222<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x04;
+    p.v[0x5] = 0xEE;
+    p.v[0x5] = p.v[0x5].wrapping_add(0x01);
+    *p.counter = if p.v[0x5] == 0xEF { 0x22E } else { 0x22C };
+    Ok(())
+}
+
+fn block_22c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_22e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x05;
+    p.v[0xF] = 0x01;
+    p.v[0x5] = 0xEE;
+    p.v[0x6] = 0xEF;
+    let (vx, borrow) = p.v[0x5].overflowing_sub(p.v[0x6]);
+    (p.v[0x5], p.v[0xF]) = (vx, !borrow as u8);
+    *p.counter = if p.v[0xF] == 0x00 { 0x23E } else { 0x23C };
+    Ok(())
+}
+
+fn block_23c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_23e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x06;
+    p.v[0xF] = 0x00;
+    p.v[0x5] = 0xEF;
+    p.v[0x6] = 0xEE;
+    let (vx, borrow) = p.v[0x5].overflowing_sub(p.v[0x6]);
+    (p.v[0x5], p.v[0xF]) = (vx, !borrow as u8);
+    *p.counter = if p.v[0xF] == 0x01 { 0x24E } else { 0x24C };
+    Ok(())
+}
+
+fn block_24c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_24e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0xF] = 0x00;
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x07;
+    p.v[0x5] = 0xEE;
+    p.v[0x6] = 0xEF;
+    let (vx, borrow) = p.v[0x6].overflowing_sub(p.v[0x5]);
+    (p.v[0x5], p.v[0xF]) = (vx, !borrow as u8);
+    *p.counter = if p.v[0xF] == 0x01 { 0x25E } else { 0x25C };
+    Ok(())
+}
+
+fn block_25c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_25e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x08;
+    p.v[0xF] = 0x01;
+    p.v[0x5] = 0xEF;
+    p.v[0x6] = 0xEE;
+    let (vx, borrow) = p.v[0x6].overflowing_sub(p.v[0x5]);
+    (p.v[0x5], p.v[0xF]) = (vx, !borrow as u8);
+    *p.counter = if p.v[0xF] == 0x00 { 0x26E } else { 0x26C };
+    Ok(())
+}
+
+fn block_26c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_26e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x00;
+    p.v[0x4] = 0x09;
+    p.v[0x5] = 0xF0;
+    p.v[0x6] = 0x0F;
+    p.v[0x5] |= p.v[0x6];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x5] == 0xFF { 0x27C } else { 0x27A };
+    Ok(())
+}
+
+fn block_27a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_27c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x00;
+    p.v[0x5] = 0xF0;
+    p.v[0x6] = 0x0F;
+    p.v[0x5] &= p.v[0x6];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x5] == 0x00 { 0x28A } else { 0x288 };
+    Ok(())
+}
+
+fn block_288<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_28a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x01;
+    p.v[0x5] = 0xF0;
+    p.v[0x6] = 0x0F;
+    p.v[0x5] ^= p.v[0x6];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x5] == 0xFF { 0x298 } else { 0x296 };
+    Ok(())
+}
+
+fn block_296<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_298<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0xF] = 0x00;
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x02;
+    p.v[0x5] = 0x81;
+    let vy = if p.quirks.shift_vy { p.v[0x0] } else { p.v[0x5] };
+    (p.v[0x5], p.v[0xF]) = (vy << 1, vy >> 7);
+    *p.counter = if p.v[0xF] == 0x01 { 0x2A6 } else { 0x2A4 };
+    Ok(())
+}
+
+fn block_2a4<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2a6<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x03;
+    p.v[0xF] = 0x01;
+    p.v[0x5] = 0x47;
+    let vy = if p.quirks.shift_vy { p.v[0x0] } else { p.v[0x5] };
+    (p.v[0x5], p.v[0xF]) = (vy << 1, vy >> 7);
+    *p.counter = if p.v[0xF] == 0x00 { 0x2B4 } else { 0x2B2 };
+    Ok(())
+}
+
+fn block_2b2<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2b4<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x04;
+    p.v[0xF] = 0x00;
+    p.v[0x5] = 0x01;
+    let vy = if p.quirks.shift_vy { p.v[0x0] } else { p.v[0x5] };
+    (p.v[0x5], p.v[0xF]) = (vy >> 1, vy & 1);
+    *p.counter = if p.v[0xF] == 0x01 { 0x2C2 } else { 0x2C0 };
+    Ok(())
+}
+
+fn block_2c0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2c2<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x05;
+    p.v[0xF] = 0x01;
+    p.v[0x5] = 0x02;
+    let vy = if p.quirks.shift_vy { p.v[0x0] } else { p.v[0x5] };
+    (p.v[0x5], p.v[0xF]) = (vy >> 1, vy & 1);
+    *p.counter = if p.v[0xF] == 0x00 { 0x2D0 } else { 0x2CE };
+    Ok(())
+}
+
+fn block_2ce<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2d0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x06;
+    p.v[0x0] = 0x15;
+    p.v[0x1] = 0x78;
+    *p.index = 0x3D0;
+    m.execute_at(0x2DA, Save(1))
+}
+
+fn block_2dc<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x2DC, Load(1))?;
+    let p = m.parts();
+    *p.counter = if p.v[0x0] == 0x15 { 0x2E2 } else { 0x2E0 };
+    Ok(())
+}
+
+fn block_2e0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2e2<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = if p.v[0x1] == 0x78 { 0x2E6 } else { 0x2E4 };
+    Ok(())
+}
+
+fn block_2e4<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2e6<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x3] = 0x01;
+    p.v[0x4] = 0x07;
+    p.v[0x0] = 0x8A;
+    *p.index = 0x3D0;
+    m.execute_at(0x2EE, AsDecimal(0))
+}
+
+fn block_2f0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x3D0;
+    m.execute_at(0x2F2, Load(0))?;
+    let p = m.parts();
+    *p.counter = if p.v[0x0] == 0x01 { 0x2F8 } else { 0x2F6 };
+    Ok(())
+}
+
+fn block_2f6<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_2f8<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x0] = 0x01;
+    *p.index = p.index.wrapping_add(p.v[0x0] as u16);
+    m.execute_at(0x2FC, Load(0))?;
+    let p = m.parts();
+    *p.counter = if p.v[0x0] == 0x03 { 0x302 } else { 0x300 };
+    Ok(())
+}
+
+fn block_300<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_302<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x0] = 0x01;
+    *p.index = p.index.wrapping_add(p.v[0x0] as u16);
+    m.execute_at(0x306, Load(0))?;
+    let p = m.parts();
+    *p.counter = if p.v[0x0] == 0x08 { 0x30C } else { 0x30A };
+    Ok(())
+}
+
+fn block_30a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x310;
+    Ok(())
+}
+
+fn block_30c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x332;
+    Ok(())
+}
+
+fn block_30e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x30E;
+    Ok(())
+}
+
+fn block_310<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x32A;
+    p.v[0x0] = 0x13;
+    p.v[0x1] = 0x09;
+    m.execute_at(0x316, Draw(0, 1, 8))?;
+    m.execute_at(0x318, GetFont(3))?;
+    let p = m.parts();
+    p.v[0x0] = 0x22;
+    p.v[0x1] = 0x0B;
+    m.execute_at(0x31E, Draw(0, 1, 5))?;
+    m.execute_at(0x320, GetFont(4))?;
+    let p = m.parts();
+    p.v[0x0] = 0x28;
+    p.v[0x1] = 0x0B;
+    m.execute_at(0x326, Draw(0, 1, 5))?;
+    let p = m.parts();
+    *p.counter = 0x30E;
+    Ok(())
+}
+
+fn block_332<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x358;
+    p.v[0x0] = 0x15;
+    p.v[0x1] = 0x0B;
+    p.v[0x3] = 0x08;
+    *p.counter = 0x33A;
+    Ok(())
+}
+
+fn block_33a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x33A, Draw(0, 1, 8))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x08);
+    *p.index = p.index.wrapping_add(p.v[0x3] as u16);
+    *p.counter = if p.v[0x0] == 0x2D { 0x344 } else { 0x342 };
+    Ok(())
+}
+
+fn block_342<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x33A;
+    Ok(())
+}
+
+fn block_344<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x370;
+    p.v[0x0] = 0x02;
+    p.v[0x1] = 0x18;
+    p.v[0x3] = 0x08;
+    *p.counter = 0x34C;
+    Ok(())
+}
+
+fn block_34c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x34C, Draw(0, 1, 8))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x05);
+    *p.index = p.index.wrapping_add(p.v[0x3] as u16);
+    *p.counter = if p.v[0x0] == 0x3E { 0x356 } else { 0x354 };
+    Ok(())
+}
+
+fn block_354<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x34C;
+    Ok(())
+}
+
+fn block_356<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x30E;
+    Ok(())
+}
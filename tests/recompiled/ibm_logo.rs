@@ -0,0 +1,47 @@
+// Recompiled by chip_oxide from a 132 byte ROM with SHA-1 1ba58656810b67fd131eb9af3e3987863bf26c90.
+// Do not edit; regenerate it instead.
+
+use chip_oxide::{Block, ChipIO, ChipOxide, Error, Instruction::*};
+
+/// Every compiled block, in address order.
+pub fn blocks<I: ChipIO>() -> Vec<Block<I>> {
+    vec![
+        Block { address: 0x200, code: &[0x00, 0xE0, 0xA2, 0x2A, 0x60, 0x0C, 0x61, 0x08, 0xD0, 0x1F, 0x70, 0x09, 0xA2, 0x39, 0xD0, 0x1F, 0xA2, 0x48, 0x70, 0x08, 0xD0, 0x1F, 0x70, 0x04, 0xA2, 0x57, 0xD0, 0x1F, 0x70, 0x08, 0xA2, 0x66, 0xD0, 0x1F, 0x70, 0x08, 0xA2, 0x75, 0xD0, 0x1F], run: block_200 },
+        Block { address: 0x228, code: &[0x12, 0x28], run: block_228 },
+    ]
+}
+
+fn block_200<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x200, Clear)?;
+    let p = m.parts();
+    *p.index = 0x22A;
+    p.v[0x0] = 0x0C;
+    p.v[0x1] = 0x08;
+    m.execute_at(0x208, Draw(0, 1, 15))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x09);
+    *p.index = 0x239;
+    m.execute_at(0x20E, Draw(0, 1, 15))?;
+    let p = m.parts();
+    *p.index = 0x248;
+    p.v[0x0] = p.v[0x0].wrapping_add(0x08);
+    m.execute_at(0x214, Draw(0, 1, 15))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x04);
+    *p.index = 0x257;
+    m.execute_at(0x21A, Draw(0, 1, 15))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x08);
+    *p.index = 0x266;
+    m.execute_at(0x220, Draw(0, 1, 15))?;
+    let p = m.parts();
+    p.v[0x0] = p.v[0x0].wrapping_add(0x08);
+    *p.index = 0x275;
+    m.execute_at(0x226, Draw(0, 1, 15))
+}
+
+fn block_228<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x228;
+    Ok(())
+}
@@ -0,0 +1,533 @@
+// Recompiled by chip_oxide from a 478 byte ROM with SHA-1 f1cfcffe1937ed6dd6eeed1a7f85dfc777bda700.
+// Do not edit; regenerate it instead.
+
+use chip_oxide::{Block, ChipIO, ChipOxide, Error, Instruction::*};
+
+/// Every compiled block, in address order.
+pub fn blocks<I: ChipIO>() -> Vec<Block<I>> {
+    vec![
+        Block { address: 0x200, code: &[0x12, 0x4E], run: block_200 },
+        Block { address: 0x242, code: &[0xA2, 0x02, 0xDA, 0xB4, 0x00, 0xEE], run: block_242 },
+        Block { address: 0x248, code: &[0xA2, 0x02, 0xDA, 0xB4, 0x13, 0xDC], run: block_248 },
+        Block { address: 0x24E, code: &[0x68, 0x01, 0x69, 0x05, 0x6A, 0x0A, 0x6B, 0x01, 0x65, 0x2A, 0x66, 0x2B, 0xA2, 0x16, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x02, 0x36, 0x2B], run: block_24e },
+        Block { address: 0x266, code: &[0xA2, 0x06], run: block_266 },
+        Block { address: 0x268, code: &[0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x1A, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x45, 0x2A], run: block_268 },
+        Block { address: 0x278, code: &[0xA2, 0x02], run: block_278 },
+        Block { address: 0x27A, code: &[0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x1E, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x55, 0x60], run: block_27a },
+        Block { address: 0x28A, code: &[0xA2, 0x02], run: block_28a },
+        Block { address: 0x28C, code: &[0xDA, 0xB4, 0x6B, 0x10, 0xA2, 0x26, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x76, 0xFF, 0x46, 0x2A], run: block_28c },
+        Block { address: 0x29E, code: &[0xA2, 0x02], run: block_29e },
+        Block { address: 0x2A0, code: &[0xDA, 0xB4, 0x6B, 0x15, 0xA2, 0x2E, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x95, 0x60], run: block_2a0 },
+        Block { address: 0x2B0, code: &[0xA2, 0x02], run: block_2b0 },
+        Block { address: 0x2B2, code: &[0xDA, 0xB4, 0x6B, 0x1A, 0xA2, 0x32, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0x22, 0x42], run: block_2b2 },
+        Block { address: 0x2C0, code: &[0x68, 0x17, 0x69, 0x1B, 0x6A, 0x20, 0x6B, 0x01, 0xA2, 0x0A, 0xD8, 0xB4, 0xA2, 0x36, 0xD9, 0xB4, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x0A, 0xD9, 0xB4, 0xA2, 0x06, 0x87, 0x50, 0x47, 0x2A], run: block_2c0 },
+        Block { address: 0x2E4, code: &[0xA2, 0x02], run: block_2e4 },
+        Block { address: 0x2E6, code: &[0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x0E, 0xD9, 0xB4, 0xA2, 0x06, 0x67, 0x2A, 0x87, 0xB1, 0x47, 0x2B], run: block_2e6 },
+        Block { address: 0x2FA, code: &[0xA2, 0x02], run: block_2fa },
+        Block { address: 0x2FC, code: &[0xDA, 0xB4, 0x6B, 0x10, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x12, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x78, 0x67, 0x1F, 0x87, 0x62, 0x47, 0x18], run: block_2fc },
+        Block { address: 0x312, code: &[0xA2, 0x02], run: block_312 },
+        Block { address: 0x314, code: &[0xDA, 0xB4, 0x6B, 0x15, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x16, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x78, 0x67, 0x1F, 0x87, 0x63, 0x47, 0x67], run: block_314 },
+        Block { address: 0x32A, code: &[0xA2, 0x02], run: block_32a },
+        Block { address: 0x32C, code: &[0xDA, 0xB4, 0x6B, 0x1A, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x1A, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x8C, 0x67, 0x8C, 0x87, 0x64, 0x47, 0x18], run: block_32c },
+        Block { address: 0x342, code: &[0xA2, 0x02], run: block_342 },
+        Block { address: 0x344, code: &[0xDA, 0xB4, 0x68, 0x2C, 0x69, 0x30, 0x6A, 0x34, 0x6B, 0x01, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x1E, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x8C, 0x67, 0x78, 0x87, 0x65, 0x47, 0xEC], run: block_344 },
+        Block { address: 0x360, code: &[0xA2, 0x02], run: block_360 },
+        Block { address: 0x362, code: &[0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x22, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0xE0, 0x86, 0x6E, 0x46, 0xC0], run: block_362 },
+        Block { address: 0x376, code: &[0xA2, 0x02], run: block_376 },
+        Block { address: 0x378, code: &[0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x36, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x0F, 0x86, 0x66, 0x46, 0x07], run: block_378 },
+        Block { address: 0x38C, code: &[0xA2, 0x02], run: block_38c },
+        Block { address: 0x38E, code: &[0xDA, 0xB4, 0x6B, 0x10, 0xA2, 0x3A, 0xD8, 0xB4, 0xA2, 0x1E, 0xD9, 0xB4, 0xA3, 0xE8, 0x60, 0x00, 0x61, 0x30, 0xF1, 0x55], run: block_38e },
+        Block { address: 0x3A2, code: &[0xA3, 0xE9, 0xF0, 0x65, 0xA2, 0x06, 0x40, 0x30], run: block_3a2 },
+        Block { address: 0x3AA, code: &[0xA2, 0x02], run: block_3aa },
+        Block { address: 0x3AC, code: &[0xDA, 0xB4, 0x6B, 0x15, 0xA2, 0x3A, 0xD8, 0xB4, 0xA2, 0x16, 0xD9, 0xB4, 0xA3, 0xE8, 0x66, 0x89, 0xF6, 0x33], run: block_3ac },
+        Block { address: 0x3BE, code: &[0xF2, 0x65, 0xA2, 0x02, 0x30, 0x01], run: block_3be },
+        Block { address: 0x3C4, code: &[0xA2, 0x06], run: block_3c4 },
+        Block { address: 0x3C6, code: &[0x31, 0x03], run: block_3c6 },
+        Block { address: 0x3C8, code: &[0xA2, 0x06], run: block_3c8 },
+        Block { address: 0x3CA, code: &[0x32, 0x07], run: block_3ca },
+        Block { address: 0x3CC, code: &[0xA2, 0x06], run: block_3cc },
+        Block { address: 0x3CE, code: &[0xDA, 0xB4, 0x6B, 0x1A, 0xA2, 0x0E, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0x12, 0x48], run: block_3ce },
+        Block { address: 0x3DC, code: &[0x13, 0xDC], run: block_3dc },
+    ]
+}
+
+fn block_200<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x24E;
+    Ok(())
+}
+
+fn block_242<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    m.execute_at(0x244, Draw(10, 11, 4))?;
+    m.execute_at(0x246, Return)
+}
+
+fn block_248<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    m.execute_at(0x24A, Draw(10, 11, 4))?;
+    let p = m.parts();
+    *p.counter = 0x3DC;
+    Ok(())
+}
+
+fn block_24e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x8] = 0x01;
+    p.v[0x9] = 0x05;
+    p.v[0xA] = 0x0A;
+    p.v[0xB] = 0x01;
+    p.v[0x5] = 0x2A;
+    p.v[0x6] = 0x2B;
+    *p.index = 0x216;
+    m.execute_at(0x25C, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x260, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = if p.v[0x6] == 0x2B { 0x268 } else { 0x266 };
+    Ok(())
+}
+
+fn block_266<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = 0x268;
+    Ok(())
+}
+
+fn block_268<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x268, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x06;
+    *p.index = 0x21A;
+    m.execute_at(0x26E, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x272, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = if p.v[0x5] != 0x2A { 0x27A } else { 0x278 };
+    Ok(())
+}
+
+fn block_278<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x27A;
+    Ok(())
+}
+
+fn block_27a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x27A, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x0B;
+    *p.index = 0x21E;
+    m.execute_at(0x280, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x284, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = if p.v[0x5] == p.v[0x6] { 0x28C } else { 0x28A };
+    Ok(())
+}
+
+fn block_28a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x28C;
+    Ok(())
+}
+
+fn block_28c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x28C, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x10;
+    *p.index = 0x226;
+    m.execute_at(0x292, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x296, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = p.v[0x6].wrapping_add(0xFF);
+    *p.counter = if p.v[0x6] != 0x2A { 0x2A0 } else { 0x29E };
+    Ok(())
+}
+
+fn block_29e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x2A0;
+    Ok(())
+}
+
+fn block_2a0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x2A0, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x15;
+    *p.index = 0x22E;
+    m.execute_at(0x2A6, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x2AA, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = if p.v[0x5] != p.v[0x6] { 0x2B2 } else { 0x2B0 };
+    Ok(())
+}
+
+fn block_2b0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x2B2;
+    Ok(())
+}
+
+fn block_2b2<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x2B2, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x1A;
+    *p.index = 0x232;
+    m.execute_at(0x2B8, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x2BC, Draw(9, 11, 4))?;
+    m.execute_at(0x2BE, SubRoutine(578))
+}
+
+fn block_2c0<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    p.v[0x8] = 0x17;
+    p.v[0x9] = 0x1B;
+    p.v[0xA] = 0x20;
+    p.v[0xB] = 0x01;
+    *p.index = 0x20A;
+    m.execute_at(0x2CA, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x236;
+    m.execute_at(0x2CE, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x202;
+    m.execute_at(0x2D2, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x06;
+    *p.index = 0x22A;
+    m.execute_at(0x2D8, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x20A;
+    m.execute_at(0x2DC, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x7] = p.v[0x5];
+    *p.counter = if p.v[0x7] != 0x2A { 0x2E6 } else { 0x2E4 };
+    Ok(())
+}
+
+fn block_2e4<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x2E6;
+    Ok(())
+}
+
+fn block_2e6<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x2E6, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x0B;
+    *p.index = 0x22A;
+    m.execute_at(0x2EC, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x20E;
+    m.execute_at(0x2F0, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x7] = 0x2A;
+    p.v[0x7] |= p.v[0xB];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x7] != 0x2B { 0x2FC } else { 0x2FA };
+    Ok(())
+}
+
+fn block_2fa<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x2FC;
+    Ok(())
+}
+
+fn block_2fc<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x2FC, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x10;
+    *p.index = 0x22A;
+    m.execute_at(0x302, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x212;
+    m.execute_at(0x306, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0x78;
+    p.v[0x7] = 0x1F;
+    p.v[0x7] &= p.v[0x6];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x7] != 0x18 { 0x314 } else { 0x312 };
+    Ok(())
+}
+
+fn block_312<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x314;
+    Ok(())
+}
+
+fn block_314<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x314, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x15;
+    *p.index = 0x22A;
+    m.execute_at(0x31A, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x216;
+    m.execute_at(0x31E, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0x78;
+    p.v[0x7] = 0x1F;
+    p.v[0x7] ^= p.v[0x6];
+    if p.quirks.vf_reset {
+        p.v[0xF] = 0;
+    }
+    *p.counter = if p.v[0x7] != 0x67 { 0x32C } else { 0x32A };
+    Ok(())
+}
+
+fn block_32a<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x32C;
+    Ok(())
+}
+
+fn block_32c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x32C, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x1A;
+    *p.index = 0x22A;
+    m.execute_at(0x332, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x21A;
+    m.execute_at(0x336, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0x8C;
+    p.v[0x7] = 0x8C;
+    let (vx, carry) = p.v[0x7].overflowing_add(p.v[0x6]);
+    (p.v[0x7], p.v[0xF]) = (vx, carry as u8);
+    *p.counter = if p.v[0x7] != 0x18 { 0x344 } else { 0x342 };
+    Ok(())
+}
+
+fn block_342<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x344;
+    Ok(())
+}
+
+fn block_344<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x344, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0x8] = 0x2C;
+    p.v[0x9] = 0x30;
+    p.v[0xA] = 0x34;
+    p.v[0xB] = 0x01;
+    *p.index = 0x22A;
+    m.execute_at(0x350, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x21E;
+    m.execute_at(0x354, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0x8C;
+    p.v[0x7] = 0x78;
+    let (vx, borrow) = p.v[0x7].overflowing_sub(p.v[0x6]);
+    (p.v[0x7], p.v[0xF]) = (vx, !borrow as u8);
+    *p.counter = if p.v[0x7] != 0xEC { 0x362 } else { 0x360 };
+    Ok(())
+}
+
+fn block_360<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x362;
+    Ok(())
+}
+
+fn block_362<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x362, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x06;
+    *p.index = 0x22A;
+    m.execute_at(0x368, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x222;
+    m.execute_at(0x36C, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0xE0;
+    let vy = p.v[0x6];
+    (p.v[0x6], p.v[0xF]) = (vy << 1, vy >> 7);
+    *p.counter = if p.v[0x6] != 0xC0 { 0x378 } else { 0x376 };
+    Ok(())
+}
+
+fn block_376<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x378;
+    Ok(())
+}
+
+fn block_378<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x378, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x0B;
+    *p.index = 0x22A;
+    m.execute_at(0x37E, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x236;
+    m.execute_at(0x382, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    p.v[0x6] = 0x0F;
+    let vy = p.v[0x6];
+    (p.v[0x6], p.v[0xF]) = (vy >> 1, vy & 1);
+    *p.counter = if p.v[0x6] != 0x07 { 0x38E } else { 0x38C };
+    Ok(())
+}
+
+fn block_38c<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x38E;
+    Ok(())
+}
+
+fn block_38e<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x38E, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x10;
+    *p.index = 0x23A;
+    m.execute_at(0x394, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x21E;
+    m.execute_at(0x398, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x3E8;
+    p.v[0x0] = 0x00;
+    p.v[0x1] = 0x30;
+    m.execute_at(0x3A0, Save(1))
+}
+
+fn block_3a2<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x3E9;
+    m.execute_at(0x3A4, Load(0))?;
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = if p.v[0x0] != 0x30 { 0x3AC } else { 0x3AA };
+    Ok(())
+}
+
+fn block_3aa<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = 0x3AC;
+    Ok(())
+}
+
+fn block_3ac<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x3AC, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x15;
+    *p.index = 0x23A;
+    m.execute_at(0x3B2, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x216;
+    m.execute_at(0x3B6, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x3E8;
+    p.v[0x6] = 0x89;
+    m.execute_at(0x3BC, AsDecimal(6))
+}
+
+fn block_3be<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x3BE, Load(2))?;
+    let p = m.parts();
+    *p.index = 0x202;
+    *p.counter = if p.v[0x0] == 0x01 { 0x3C6 } else { 0x3C4 };
+    Ok(())
+}
+
+fn block_3c4<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = 0x3C6;
+    Ok(())
+}
+
+fn block_3c6<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = if p.v[0x1] == 0x03 { 0x3CA } else { 0x3C8 };
+    Ok(())
+}
+
+fn block_3c8<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = 0x3CA;
+    Ok(())
+}
+
+fn block_3ca<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = if p.v[0x2] == 0x07 { 0x3CE } else { 0x3CC };
+    Ok(())
+}
+
+fn block_3cc<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.index = 0x206;
+    *p.counter = 0x3CE;
+    Ok(())
+}
+
+fn block_3ce<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    m.execute_at(0x3CE, Draw(10, 11, 4))?;
+    let p = m.parts();
+    p.v[0xB] = 0x1A;
+    *p.index = 0x20E;
+    m.execute_at(0x3D4, Draw(8, 11, 4))?;
+    let p = m.parts();
+    *p.index = 0x23E;
+    m.execute_at(0x3D8, Draw(9, 11, 4))?;
+    let p = m.parts();
+    *p.counter = 0x248;
+    Ok(())
+}
+
+fn block_3dc<I: ChipIO>(m: &mut ChipOxide<I>) -> Result<(), Error> {
+    let p = m.parts();
+    *p.counter = 0x3DC;
+    Ok(())
+}